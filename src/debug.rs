@@ -1,4 +1,8 @@
-use bevy::{input::common_conditions::input_toggle_active, prelude::*};
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    input::common_conditions::input_toggle_active,
+    prelude::*,
+};
 use bevy_infinite_grid::{InfiniteGridBundle, InfiniteGridPlugin};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
@@ -12,6 +16,8 @@ impl Plugin for DebugPlugin {
             WorldInspectorPlugin::default().run_if(input_toggle_active(true, KeyCode::Escape)),
         )
         .add_plugins(InfiniteGridPlugin)
+        // frame times in the log, to keep an eye on the budget during an F8 swarm
+        .add_plugins((FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin::default()))
         .add_systems(OnEnter(GameState::Game), spawn_grid);
     }
 }
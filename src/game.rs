@@ -1,11 +1,21 @@
 mod camera;
+mod combo;
 mod dice_physics;
 mod economy;
+// the simulator is run from the command line, which the web build does not have
+#[cfg(not(target_arch = "wasm32"))]
+mod fairness;
+mod flow_field;
+mod forge;
+mod history;
+mod hud;
 mod placement;
+mod resolution;
 mod roll;
+mod throw;
 mod wave;
 
-use super::GameState;
+use super::{despawn_screen, GameState};
 
 use avian3d::prelude::*;
 use bevy::math::vec2;
@@ -15,17 +25,35 @@ use bevy::{ecs::system::SystemState, gltf::Gltf, render::primitives::Aabb};
 use bevy_asset_loader::prelude::*;
 use bevy_common_assets::ron::RonAssetPlugin;
 use camera::CameraPlugin;
+use combo::ComboPlugin;
 use economy::EconomyPlugin;
-use placement::PlacementPlugin;
+#[cfg(not(target_arch = "wasm32"))]
+pub use fairness::{simulate_fairness, FairnessConfig};
+use flow_field::FlowFieldPlugin;
+use history::HistoryPlugin;
+use hud::HudPlugin;
+use placement::{PlacementPlugin, Projectile, Tower};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::{thread_rng, Rng};
+use rand::{Rng, SeedableRng};
+use resolution::ResolutionPlugin;
 use roll::RollPlugin;
 use std::f32::consts::PI;
+use throw::Throw;
+use uuid::Uuid;
 use vleue_navigator::prelude::*;
-use wave::WavePlugin;
+use wave::{Enemy, EnemySpawner, WavePlugin};
 
 const SNAP_OFFSET: f32 = 0.5;
 
+// Outer edges of the walkable area, in navmesh space.
+const NAVMESH_BOUNDS: [Vec2; 4] = [
+    vec2(-20.0, -20.0),
+    vec2(20.0, -20.0),
+    vec2(20.0, 20.0),
+    vec2(-20.0, 20.0),
+];
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
@@ -33,8 +61,13 @@ impl Plugin for GamePlugin {
         app.init_state::<GamePlayState>()
             .add_plugins((
                 CameraPlugin,
+                ComboPlugin,
                 EconomyPlugin,
+                FlowFieldPlugin,
+                HistoryPlugin,
+                HudPlugin,
                 PlacementPlugin,
+                ResolutionPlugin,
                 RollPlugin,
                 WavePlugin,
                 PhysicsPlugins::default(),
@@ -46,13 +79,33 @@ impl Plugin for GamePlugin {
             ))
             .init_resource::<Assets<TowerDetails>>()
             .init_resource::<Assets<EnemyDetails>>()
+            .init_resource::<Assets<WaveDetails>>()
+            .init_resource::<Assets<ElementMatchup>>()
+            .init_resource::<Assets<DieTemplate>>()
+            .init_resource::<ElementChart>()
             .init_resource::<GameResources>()
+            .insert_resource(RunSeed::from_args())
+            .insert_resource(RunRng(StdRng::seed_from_u64(0)))
             .register_type::<GameResources>()
-            .register_type::<uuid::Uuid>()
+            .register_type::<Uuid>()
             .add_event::<DiePurchaseEvent>()
             .add_event::<DieRolledEvent>()
             .add_event::<DieRollResultEvent>()
-            .add_systems(OnEnter(GameState::Game), setup)
+            .add_systems(
+                OnEnter(GameState::Game),
+                (seed_run, setup, build_element_chart),
+            )
+            .add_systems(
+                OnExit(GameState::Game),
+                (
+                    despawn_screen::<OnGameScreen>,
+                    despawn_screen::<Tower>,
+                    despawn_screen::<Enemy>,
+                    despawn_screen::<Projectile>,
+                    despawn_screen::<EnemySpawner>,
+                    reset_gameplay_state,
+                ),
+            )
             .add_systems(
                 Update,
                 (die_purchased, save_die_result).run_if(in_state(GameState::Game)),
@@ -66,6 +119,8 @@ enum GamePlayState {
     #[default]
     Economy,
     Rolling,
+    // bonuses from the dice rolled together, before their towers are placed
+    Combo,
     Placement,
     Wave,
 }
@@ -73,6 +128,10 @@ enum GamePlayState {
 #[derive(Component, Debug)]
 struct Obstacle;
 
+// Tag component used to tag entities that live as long as a run does
+#[derive(Component)]
+struct OnGameScreen;
+
 #[derive(AssetCollection, Resource)]
 #[allow(dead_code)]
 pub struct AllAssets {
@@ -80,6 +139,12 @@ pub struct AllAssets {
     pub towers: Vec<Handle<TowerDetails>>,
     #[asset(key = "enemies", collection(typed))]
     pub enemies: Vec<Handle<EnemyDetails>>,
+    #[asset(key = "waves", collection(typed))]
+    pub waves: Vec<Handle<WaveDetails>>,
+    #[asset(key = "elements", collection(typed))]
+    pub elements: Vec<Handle<ElementMatchup>>,
+    #[asset(key = "dice", collection(typed))]
+    pub dice: Vec<Handle<DieTemplate>>,
 }
 
 #[derive(Resource, Debug, Clone, PartialEq, Reflect)]
@@ -88,8 +153,16 @@ pub struct GameResources {
     money: usize,
     dice: Vec<Die>,
     highlighted_die: usize,
-    towers: Vec<AssetId<TowerDetails>>,
+    towers: Vec<OwnedTower>,
     highlighted_tower: usize,
+    // number of waves cleared, which is also the index of the next wave
+    wave: usize,
+    // health of the base, the run is over once leaking enemies bring it to zero
+    lives: u32,
+    // results that can still be thrown away and rolled again this round
+    rerolls: u32,
+    // rerolls bought in the shop, added to the next round's
+    bought_rerolls: u32,
 }
 
 impl Default for GameResources {
@@ -100,31 +173,152 @@ impl Default for GameResources {
             highlighted_die: 0,
             towers: Vec::new(),
             highlighted_tower: 0,
+            wave: 0,
+            lives: 20,
+            rerolls: 0,
+            bought_rerolls: 0,
+        }
+    }
+}
+
+impl GameResources {
+    pub fn money(&self) -> usize {
+        self.money
+    }
+
+    pub fn wave(&self) -> usize {
+        self.wave
+    }
+}
+
+/// Seed every random decision of a run is drawn from, so the same seed replays the same run.
+///
+/// Set with `--seed <n>` on the command line or from the menu, a fresh one is picked otherwise.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunSeed(pub u64);
+
+impl RunSeed {
+    fn from_args() -> Self {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg != "--seed" {
+                continue;
+            }
+            match args.next().map(|seed| seed.parse()) {
+                Some(Ok(seed)) => return RunSeed(seed),
+                _ => warn!("--seed expects a number, picking a random seed"),
+            }
         }
+        RunSeed(rand::random())
     }
 }
 
+/// Random number generator of the current run, reseeded from [`RunSeed`] whenever a run starts.
+#[derive(Resource)]
+pub struct RunRng(StdRng);
+
+/// A tower waiting in the player's pool, at the rarity of the face it was rolled from.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+struct OwnedTower {
+    details: AssetId<TowerDetails>,
+    rarity: Rarity,
+    // the die whose result it was rolled from this round, so a reroll can take it back
+    source_die: Option<Uuid>,
+}
+
 /// Representation of a loaded tower file.
 #[derive(Asset, Resource, Component, Debug, PartialEq, Clone, TypePath)]
 pub struct TowerDetails {
     pub name: String,
     pub element_type: BaseElementType,
+    /// Other element of a hybrid tower, e.g. Water on a Fire tower makes Steam.
+    pub secondary_element: Option<BaseElementType>,
+    /// The least rare die face that can resolve into this tower.
+    pub min_rarity: Rarity,
+    /// Combat stats of a common tower, see [`TowerStats::scaled`].
+    pub stats: TowerStats,
+    pub projectile_mesh: Handle<Mesh>,
+    pub projectile_material: Handle<StandardMaterial>,
     pub model: Handle<Gltf>,
 }
 
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct TowerStats {
+    pub range: f32,
+    pub damage: u32,
+    // shots per second
+    pub fire_rate: f32,
+    pub projectile_speed: f32,
+}
+
+impl TowerStats {
+    /// Stats of a tower rolled at the given rarity. Range grows at half the rate of the rest so
+    /// rare towers do not cover the whole map.
+    pub fn scaled(&self, rarity: Rarity) -> Self {
+        let multiplier = rarity.multiplier();
+        TowerStats {
+            range: self.range * (1.0 + (multiplier - 1.0) / 2.0),
+            damage: (self.damage as f32 * multiplier).round() as u32,
+            fire_rate: self.fire_rate * multiplier,
+            projectile_speed: self.projectile_speed * multiplier,
+        }
+    }
+}
+
 /// Representation of a loaded enemy file.
 #[derive(Asset, Debug, TypePath)]
 pub struct EnemyDetails {
     pub name: String,
     pub health: u32,
     pub speed: f32,
+    /// Lives lost when this enemy reaches the goal.
+    pub damage: u32,
+    pub element_type: BaseElementType,
     pub model: Handle<Gltf>,
 }
 
+/// Representation of a loaded wave, spawned group by group by the wave director.
+#[derive(Asset, Debug, TypePath)]
+pub struct WaveDetails {
+    pub groups: Vec<WaveGroupRon>,
+}
+
+/// How hard attacks of one element hit enemies of another, see [`ElementChart`].
+#[derive(serde::Deserialize, Asset, Debug, TypePath, Clone, Copy)]
+pub struct ElementMatchup {
+    pub attacker: BaseElementType,
+    pub defender: BaseElementType,
+    pub multiplier: f32,
+}
+
+/// Representation of a loaded die template, the shop sells a freshly rolled die of each.
+#[derive(Asset, Debug, TypePath)]
+pub struct DieTemplate {
+    pub name: String,
+    pub shape: DieShape,
+    pub base_element: BaseElementType,
+    pub base_rarity: Rarity,
+    /// Chance for each face to be of a different element than the base one.
+    pub element_chance: f64,
+    /// Chance for each face to be one rarity above the base one.
+    pub rarity_chance: f64,
+    /// Chance for a common face to carry a second element, multiplied for every rarity above.
+    pub secondary_chance: f64,
+    pub price: usize,
+    /// Scene of the die, the shape's own geometry is used without one.
+    pub model: Option<Handle<Scene>>,
+    /// Weight of the ballast loading a random face, as a share of the die's own. Zero is a fair
+    /// die.
+    pub load: f32,
+}
+
 #[derive(serde::Deserialize, Debug, Clone)]
 enum CustomDynamicAsset {
     Towers(Vec<TowerDetailsRon>),
     Enemies(Vec<EnemyDetailsRon>),
+    Waves(Vec<WaveDetailsRon>),
+    Elements(Vec<ElementMatchup>),
+    Dice(Vec<DieTemplateRon>),
 }
 
 impl DynamicAsset for CustomDynamicAsset {
@@ -138,6 +332,16 @@ impl DynamicAsset for CustomDynamicAsset {
                 .iter()
                 .map(|enemy| asset_server.load::<Gltf>(enemy.model.clone()).untyped())
                 .collect(),
+            CustomDynamicAsset::Dice(dice) => dice
+                .iter()
+                .filter_map(|die| die.model.clone())
+                .map(|model| {
+                    asset_server
+                        .load::<Scene>(GltfAssetLabel::Scene(0).from_asset(model))
+                        .untyped()
+                })
+                .collect(),
+            CustomDynamicAsset::Waves(_) | CustomDynamicAsset::Elements(_) => vec![],
         }
     }
 
@@ -150,11 +354,37 @@ impl DynamicAsset for CustomDynamicAsset {
                         .get_resource::<AssetServer>()
                         .unwrap()
                         .load(tower.model.clone());
+                    let projectile_mesh = world
+                        .get_resource_mut::<Assets<Mesh>>()
+                        .unwrap()
+                        .add(Sphere::new(tower.projectile.radius));
+                    let (r, g, b) = tower.projectile.color;
+                    let projectile_material = world
+                        .get_resource_mut::<Assets<StandardMaterial>>()
+                        .unwrap()
+                        .add(StandardMaterial {
+                            base_color: Color::srgb(r, g, b),
+                            ..Default::default()
+                        });
+                    let mut stats = tower.stats;
+                    // shots are timed as 1 / fire rate, so it has to be a positive number
+                    if !(stats.fire_rate > 0.0 && stats.fire_rate.is_finite()) {
+                        warn!(
+                            "{} has a fire rate of {}, falling back to one shot a second",
+                            tower.name, stats.fire_rate
+                        );
+                        stats.fire_rate = 1.0;
+                    }
                     let mut tower_details =
                         SystemState::<ResMut<Assets<TowerDetails>>>::new(world).get_mut(world);
                     let handle = tower_details.add(TowerDetails {
                         name: tower.name.clone(),
                         element_type: tower.element_type,
+                        secondary_element: tower.secondary_element,
+                        min_rarity: tower.min_rarity,
+                        stats,
+                        projectile_mesh,
+                        projectile_material,
                         model: model.clone(),
                     });
                     towers_collection.push(handle.untyped());
@@ -174,6 +404,8 @@ impl DynamicAsset for CustomDynamicAsset {
                         name: enemy.name.clone(),
                         health: enemy.health,
                         speed: enemy.speed,
+                        damage: enemy.damage,
+                        element_type: enemy.element_type,
                         model: model.clone(),
                     });
                     enemies_collection.push(handle.untyped());
@@ -181,25 +413,181 @@ impl DynamicAsset for CustomDynamicAsset {
                 }
                 Ok(DynamicAssetType::Collection(enemies_collection))
             }
+            CustomDynamicAsset::Waves(waves) => {
+                let mut waves_collection = vec![];
+                for (i, wave) in waves.iter().enumerate() {
+                    let mut assets = world.get_resource_mut::<Assets<WaveDetails>>().unwrap();
+                    let handle = assets.add(WaveDetails {
+                        groups: wave.groups.clone(),
+                    });
+                    waves_collection.push(handle.untyped());
+                    info!("Built wave: {}", i + 1);
+                }
+                Ok(DynamicAssetType::Collection(waves_collection))
+            }
+            CustomDynamicAsset::Elements(matchups) => {
+                let mut matchups_collection = vec![];
+                for matchup in matchups {
+                    let mut assets = world.get_resource_mut::<Assets<ElementMatchup>>().unwrap();
+                    let handle = assets.add(*matchup);
+                    matchups_collection.push(handle.untyped());
+                    info!(
+                        "Built matchup: {} vs {}",
+                        matchup.attacker, matchup.defender
+                    );
+                }
+                Ok(DynamicAssetType::Collection(matchups_collection))
+            }
+            CustomDynamicAsset::Dice(dice) => {
+                let mut dice_collection = vec![];
+                for die in dice {
+                    let model = die.model.clone().map(|model| {
+                        world
+                            .get_resource::<AssetServer>()
+                            .unwrap()
+                            .load(GltfAssetLabel::Scene(0).from_asset(model))
+                    });
+                    let mut assets = world.get_resource_mut::<Assets<DieTemplate>>().unwrap();
+                    let handle = assets.add(DieTemplate {
+                        name: die.name.clone(),
+                        shape: die.shape,
+                        base_element: die.base_element,
+                        base_rarity: die.base_rarity,
+                        element_chance: checked_chance(
+                            &die.name,
+                            "element_chance",
+                            die.element_chance,
+                        ),
+                        rarity_chance: checked_chance(
+                            &die.name,
+                            "rarity_chance",
+                            die.rarity_chance,
+                        ),
+                        secondary_chance: checked_chance(
+                            &die.name,
+                            "secondary_chance",
+                            die.secondary_chance,
+                        ),
+                        price: die.price,
+                        model,
+                        load: die.load,
+                    });
+                    dice_collection.push(handle.untyped());
+                    info!("Built die: {}", die.name);
+                }
+                Ok(DynamicAssetType::Collection(dice_collection))
+            }
         }
     }
 }
 
+// chances are rolled with `gen_bool`, which panics on anything outside 0..=1
+fn checked_chance(die: &str, field: &str, chance: f64) -> f64 {
+    if (0.0..=1.0).contains(&chance) {
+        return chance;
+    }
+    let clamped = if chance.is_nan() {
+        0.0
+    } else {
+        chance.clamp(0.0, 1.0)
+    };
+    warn!(
+        "{} has a {} of {}, it must be between 0 and 1, using {}",
+        die, field, chance, clamped
+    );
+    clamped
+}
+
 #[derive(serde::Deserialize, Asset, Debug, TypePath, Clone)]
 pub struct TowerDetailsRon {
     pub name: String,
     pub element_type: BaseElementType,
+    // the other element of a hybrid tower, rolled from faces carrying both
+    #[serde(default)]
+    pub secondary_element: Option<BaseElementType>,
+    #[serde(default)]
+    pub min_rarity: Rarity,
+    pub stats: TowerStats,
+    pub projectile: ProjectileVisualRon,
     pub model: String,
 }
 
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct ProjectileVisualRon {
+    pub radius: f32,
+    // linear rgb, each channel in 0..=1
+    pub color: (f32, f32, f32),
+}
+
 #[derive(serde::Deserialize, Asset, Debug, TypePath, Clone)]
 pub struct EnemyDetailsRon {
     pub name: String,
     pub health: u32,
     pub speed: f32,
+    #[serde(default = "default_enemy_damage")]
+    pub damage: u32,
+    #[serde(default)]
+    pub element_type: BaseElementType,
     pub model: String,
 }
 
+fn default_enemy_damage() -> u32 {
+    1
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct DieTemplateRon {
+    pub name: String,
+    pub shape: DieShape,
+    pub base_element: BaseElementType,
+    #[serde(default)]
+    pub base_rarity: Rarity,
+    #[serde(default = "default_element_chance")]
+    pub element_chance: f64,
+    #[serde(default = "default_rarity_chance")]
+    pub rarity_chance: f64,
+    #[serde(default = "default_secondary_chance")]
+    pub secondary_chance: f64,
+    pub price: usize,
+    // a glb for the die, faces ordered like the shape's face normals
+    #[serde(default)]
+    pub model: Option<String>,
+    // ballast weight of a loaded die, one weighs the ballast as much as the die
+    #[serde(default)]
+    pub load: f32,
+}
+
+fn default_element_chance() -> f64 {
+    0.25
+}
+
+fn default_rarity_chance() -> f64 {
+    0.05
+}
+
+fn default_secondary_chance() -> f64 {
+    0.02
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct WaveDetailsRon {
+    pub groups: Vec<WaveGroupRon>,
+}
+
+/// A run of identical enemies leaving one spawn point at a fixed interval.
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct WaveGroupRon {
+    pub enemy: String,
+    pub count: u32,
+    // seconds between two enemies of the group
+    pub interval: f32,
+    // seconds after the wave starts before the first enemy spawns
+    #[serde(default)]
+    pub delay: f32,
+    #[serde(default)]
+    pub spawn_point: usize,
+}
+
 #[derive(AssetCollection, Resource)]
 pub struct GltfAssets {
     #[asset(path = "models/dungeon.glb#Scene0")]
@@ -220,35 +608,35 @@ impl DynamicAssetCollection for AssetCollections {
 #[derive(Default, Component)]
 struct Goal;
 
+/// Where enemies enter the map on their way to the [`Goal`], referenced by index from waves.
+#[derive(Default, Component)]
+struct SpawnPoint(usize);
+
 #[derive(Resource, Debug, Clone, PartialEq, Copy, Reflect)]
 #[reflect(Resource)]
 struct DieFace {
     primary_type: BaseElementType,
+    // a second element on the face, which resolves into a hybrid tower
+    secondary_type: Option<BaseElementType>,
     rarity: Rarity,
 }
 
 impl DieFace {
-    pub fn new(primary_type: BaseElementType, rarity: Rarity) -> Self {
-        DieFace {
-            primary_type,
-            rarity,
-        }
-    }
-
-    pub fn generate(base_element: BaseElementType, base_rarity: Rarity) -> Self {
-        let mut rng = thread_rng();
+    pub fn generate(template: &DieTemplate, rng: &mut impl Rng) -> Self {
+        let base_element = template.base_element;
+        let base_rarity = template.base_rarity;
+        let elements = [
+            BaseElementType::Earth,
+            BaseElementType::Fire,
+            BaseElementType::Water,
+            BaseElementType::Wind,
+        ];
 
         // chance to change element type
-        let final_element = if rng.gen_bool(0.25) {
-            let elements = [
-                BaseElementType::Earth,
-                BaseElementType::Fire,
-                BaseElementType::Water,
-                BaseElementType::Wind,
-            ];
+        let final_element = if rng.gen_bool(template.element_chance) {
             // Keep rolling until we get a different element
             loop {
-                let new_element = *elements.choose(&mut rng).unwrap();
+                let new_element = *elements.choose(rng).unwrap();
                 if new_element != base_element {
                     break new_element;
                 }
@@ -258,26 +646,44 @@ impl DieFace {
         };
 
         // chance to upgrade rarity
-        let final_rarity = if rng.gen_bool(0.05) {
-            match base_rarity {
-                Rarity::Common => Rarity::Uncommon,
-                Rarity::Uncommon => Rarity::Rare,
-                Rarity::Rare => Rarity::Epic,
-                Rarity::Epic => Rarity::Unique,
-                Rarity::Unique => Rarity::Unique,
-            }
+        let final_rarity = if rng.gen_bool(template.rarity_chance) {
+            base_rarity.upgraded()
         } else {
             base_rarity
         };
 
+        // chance of a second element, more likely the rarer the face
+        let secondary_chance = template.secondary_chance * (final_rarity as usize + 1) as f64;
+        let secondary_type = (final_element != BaseElementType::None
+            && rng.gen_bool(secondary_chance.clamp(0.0, 1.0)))
+        .then(|| {
+            let others = elements
+                .into_iter()
+                .filter(|element| *element != final_element)
+                .collect::<Vec<_>>();
+            *others.choose(rng).unwrap()
+        });
+
         DieFace {
             primary_type: final_element,
+            secondary_type,
             rarity: final_rarity,
         }
     }
 }
 
-#[derive(Resource, serde::Deserialize, Default, Debug, Clone, Copy, PartialEq, Reflect)]
+impl std::fmt::Display for DieFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.secondary_type {
+            Some(secondary) => write!(f, "{}/{}", self.primary_type, secondary),
+            None => write!(f, "{}", self.primary_type),
+        }
+    }
+}
+
+#[derive(
+    Resource, serde::Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect,
+)]
 #[reflect(Resource)]
 pub enum BaseElementType {
     #[default]
@@ -300,9 +706,55 @@ impl std::fmt::Display for BaseElementType {
     }
 }
 
-#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+impl BaseElementType {
+    pub const ALL: [BaseElementType; 5] = [
+        BaseElementType::None,
+        BaseElementType::Fire,
+        BaseElementType::Water,
+        BaseElementType::Earth,
+        BaseElementType::Wind,
+    ];
+}
+
+/// Lookup table built from the `elements` matchups in game.ron. Pairs that are not listed deal
+/// normal damage.
+#[derive(Resource, Debug, Default)]
+pub struct ElementChart(HashMap<(BaseElementType, BaseElementType), f32>);
+
+impl ElementChart {
+    pub fn multiplier(&self, attacker: BaseElementType, defender: BaseElementType) -> f32 {
+        self.0.get(&(attacker, defender)).copied().unwrap_or(1.0)
+    }
+
+    /// Multiplier of a hybrid attacker, which hits with whichever of its elements does better.
+    pub fn best_multiplier(
+        &self,
+        attacker: BaseElementType,
+        secondary: Option<BaseElementType>,
+        defender: BaseElementType,
+    ) -> f32 {
+        std::iter::once(attacker)
+            .chain(secondary)
+            .map(|element| self.multiplier(element, defender))
+            .fold(f32::MIN, f32::max)
+    }
+}
+
+#[derive(
+    Resource,
+    serde::Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Reflect,
+)]
 #[reflect(Resource)]
-enum Rarity {
+pub enum Rarity {
     #[default]
     Common,
     Uncommon,
@@ -311,6 +763,49 @@ enum Rarity {
     Unique,
 }
 
+impl Rarity {
+    pub const ALL: [Rarity; 5] = [
+        Rarity::Common,
+        Rarity::Uncommon,
+        Rarity::Rare,
+        Rarity::Epic,
+        Rarity::Unique,
+    ];
+
+    /// How much stronger than a common one a tower of this rarity is.
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            Rarity::Common => 1.0,
+            Rarity::Uncommon => 1.2,
+            Rarity::Rare => 1.45,
+            Rarity::Epic => 1.75,
+            Rarity::Unique => 2.2,
+        }
+    }
+
+    /// The next rarity up, unique is as rare as it gets.
+    pub fn upgraded(&self) -> Self {
+        match self {
+            Rarity::Common => Rarity::Uncommon,
+            Rarity::Uncommon => Rarity::Rare,
+            Rarity::Rare => Rarity::Epic,
+            Rarity::Epic => Rarity::Unique,
+            Rarity::Unique => Rarity::Unique,
+        }
+    }
+
+    /// What the forge charges to work a face of this rarity, doubling with every step up.
+    pub fn forge_price(&self) -> usize {
+        match self {
+            Rarity::Common => 10,
+            Rarity::Uncommon => 20,
+            Rarity::Rare => 40,
+            Rarity::Epic => 80,
+            Rarity::Unique => 160,
+        }
+    }
+}
+
 impl std::fmt::Display for Rarity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -326,16 +821,62 @@ impl std::fmt::Display for Rarity {
 #[derive(Event)]
 struct DiePurchaseEvent(Die);
 
+// the id of the owned die to throw and how it is thrown
 #[derive(Event)]
-struct DieRolledEvent(Die);
+struct DieRolledEvent(Uuid, Throw);
 
+// the id of the die that was thrown, the face it landed on with its index in the die's faces, and
+// the throw it was thrown with
 #[derive(Event)]
-struct DieRollResultEvent(Die, DieFace);
+struct DieRollResultEvent(Uuid, DieFace, usize, Throw);
+
+/// The polyhedron a die is cast as, which decides how many faces it has.
+#[derive(serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum DieShape {
+    D4,
+    #[default]
+    D6,
+    D8,
+    D12,
+    D20,
+}
+
+impl DieShape {
+    // listed for the fairness simulator's command line, which the web build does not have
+    #[cfg(not(target_arch = "wasm32"))]
+    pub const ALL: [DieShape; 5] = [
+        DieShape::D4,
+        DieShape::D6,
+        DieShape::D8,
+        DieShape::D12,
+        DieShape::D20,
+    ];
+
+    pub fn face_count(&self) -> usize {
+        match self {
+            DieShape::D4 => 4,
+            DieShape::D6 => 6,
+            DieShape::D8 => 8,
+            DieShape::D12 => 12,
+            DieShape::D20 => 20,
+        }
+    }
+}
+
+impl std::fmt::Display for DieShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "d{}", self.face_count())
+    }
+}
 
 #[derive(Resource, Debug, Clone, Reflect)]
 #[reflect(Resource)]
 struct Die {
-    // the faces of the die
+    // tells apart dice that have the same faces
+    id: Uuid,
+    name: String,
+    shape: DieShape,
+    // the faces of the die, ordered like the shape's face normals
     faces: Vec<DieFace>,
     // the current monetary value of the die
     value: usize,
@@ -343,46 +884,86 @@ struct Die {
     result: Option<DieFace>,
     // whether the die is currently being rolled
     rolling: bool,
+    // scene thrown when rolling, the shape's own geometry without one
+    model: Option<Handle<Scene>>,
+    // ballast making one face come up more often, fair dice have none
+    load: Option<DieLoad>,
+}
+
+/// Ballast set into a die so one face lands on top more often than the others.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct DieLoad {
+    /// Index of the favoured face in the die's faces.
+    pub face: usize,
+    /// Weight of the ballast as a share of the die's own.
+    pub weight: f32,
 }
 
 impl PartialEq for Die {
     fn eq(&self, other: &Self) -> bool {
-        self.faces == other.faces
+        self.id == other.id
     }
 }
 
-struct DieBuilder {
-    base_face: DieFace,
-    size: usize,
+impl Die {
+    /// A copy of this die with an id of its own, e.g. the one taken home from the shop.
+    fn duplicate(&self, rng: &mut impl Rng) -> Die {
+        Die {
+            id: new_die_id(rng),
+            ..self.clone()
+        }
+    }
 }
 
-impl DieBuilder {
-    pub fn from_d6_type(selected_type: BaseElementType) -> Self {
-        DieBuilder {
-            base_face: DieFace::new(selected_type, Rarity::Common),
-            size: 6,
-        }
+// ids come from the run's rng so a replayed run hands out the same ones
+fn new_die_id(rng: &mut impl Rng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.gen()).into_uuid()
+}
+
+struct DieBuilder<'a> {
+    template: &'a DieTemplate,
+}
+
+impl<'a> DieBuilder<'a> {
+    pub fn from_template(template: &'a DieTemplate) -> Self {
+        DieBuilder { template }
     }
 
-    fn build(self) -> Die {
+    fn build(self, rng: &mut impl Rng) -> Die {
         let mut faces = Vec::new();
-        for _ in 0..self.size {
-            faces.push(DieFace::generate(
-                self.base_face.primary_type,
-                self.base_face.rarity,
-            ));
+        for _ in 0..self.template.shape.face_count() {
+            faces.push(DieFace::generate(self.template, rng));
         }
 
+        // the face a loaded die favours is picked at random like its faces are
+        let load = (self.template.load > 0.0).then(|| DieLoad {
+            face: rng.gen_range(0..faces.len()),
+            weight: self.template.load,
+        });
+
         Die {
+            id: new_die_id(rng),
+            name: self.template.name.clone(),
+            shape: self.template.shape,
             faces,
-            value: 20,
+            value: self.template.price,
             result: None,
             rolling: false,
+            model: self.template.model.clone(),
+            load,
         }
     }
 }
 
+fn seed_run(seed: Res<RunSeed>, mut rng: ResMut<RunRng>) {
+    info!("Starting run with seed {}", seed.0);
+    rng.0 = StdRng::seed_from_u64(seed.0);
+}
+
 fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, gltfassets: Res<GltfAssets>) {
+    // every run starts from scratch, the previous one is kept around for the game over summary
+    commands.insert_resource(GameResources::default());
+
     commands.spawn((
         DirectionalLight {
             illuminance: light_consts::lux::OVERCAST_DAY,
@@ -395,12 +976,14 @@ fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, gltfassets: R
             ..default()
         },
         Name::new("Directional Light"),
+        OnGameScreen,
     ));
 
     commands.spawn((
         SceneRoot(gltfassets.dungeon.clone()),
         RigidBody::Static,
         ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
+        OnGameScreen,
     ));
 
     // spawn square placeholder for goal
@@ -410,29 +993,46 @@ fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, gltfassets: R
             .with_translation(Vec3::new(-3.9, 0.0, -1.5))
             .with_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
         Goal,
+        OnGameScreen,
+    ));
+
+    commands.spawn((
+        Transform::from_translation(Vec3::new(3.9, 0.0, 1.5)),
+        SpawnPoint(0),
+        Name::new("Spawn Point 0"),
+        OnGameScreen,
     ));
 
     commands.spawn((
         NavMeshSettings {
             // Define the outer borders of the navmesh.
-            fixed: Triangulation::from_outer_edges(&[
-                vec2(-20.0, -20.0),
-                vec2(20.0, -20.0),
-                vec2(20.0, 20.0),
-                vec2(-20.0, 20.0),
-            ]),
+            fixed: Triangulation::from_outer_edges(&NAVMESH_BOUNDS),
             ..default()
         },
         // Mark it for update as soon as obstacles are changed.
         // Other modes can be debounced or manually triggered.
         NavMeshUpdateMode::Debounced(0.2),
         Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+        OnGameScreen,
     ));
 }
 
-#[derive(Default, Component)]
-struct Wave {
-    timer: Timer,
+fn build_element_chart(
+    mut chart: ResMut<ElementChart>,
+    all_assets: Res<AllAssets>,
+    assets_matchups: Res<Assets<ElementMatchup>>,
+) {
+    chart.0 = all_assets
+        .elements
+        .iter()
+        .filter_map(|handle| assets_matchups.get(handle))
+        .map(|matchup| ((matchup.attacker, matchup.defender), matchup.multiplier))
+        .collect();
+}
+
+// the next run starts back in the shop, whichever phase this one ended in
+fn reset_gameplay_state(mut next_state: ResMut<NextState<GamePlayState>>) {
+    next_state.set(GamePlayState::Economy);
 }
 
 fn die_purchased(
@@ -450,7 +1050,7 @@ fn save_die_result(
 ) {
     for ev in ev_result.read() {
         for die in game_resources.dice.iter_mut() {
-            if *die == ev.0 {
+            if die.id == ev.0 {
                 die.result = Some(ev.1);
                 die.rolling = false;
                 break;
@@ -458,3 +1058,48 @@ fn save_die_result(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATS: TowerStats = TowerStats {
+        range: 4.0,
+        damage: 5,
+        fire_rate: 1.0,
+        projectile_speed: 10.0,
+    };
+
+    #[test]
+    fn common_towers_keep_their_stats() {
+        assert_eq!(STATS.scaled(Rarity::Common), STATS);
+    }
+
+    #[test]
+    fn range_grows_at_half_the_rate_of_the_rest() {
+        let unique = STATS.scaled(Rarity::Unique);
+        assert_eq!(unique.damage, 11);
+        assert!((unique.fire_rate - 2.2).abs() < 1e-5);
+        assert!((unique.projectile_speed - 22.0).abs() < 1e-4);
+        assert!((unique.range - 6.4).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rarer_towers_are_never_weaker() {
+        for pair in Rarity::ALL.windows(2) {
+            let (lower, higher) = (STATS.scaled(pair[0]), STATS.scaled(pair[1]));
+            assert!(higher.range > lower.range);
+            assert!(higher.damage >= lower.damage);
+            assert!(higher.fire_rate > lower.fire_rate);
+            assert!(higher.projectile_speed > lower.projectile_speed);
+        }
+    }
+
+    #[test]
+    fn chances_are_kept_between_zero_and_one() {
+        assert_eq!(checked_chance("d6", "element_chance", 0.25), 0.25);
+        assert_eq!(checked_chance("d6", "element_chance", 1.5), 1.0);
+        assert_eq!(checked_chance("d6", "rarity_chance", -0.1), 0.0);
+        assert_eq!(checked_chance("d6", "rarity_chance", f64::NAN), 0.0);
+    }
+}
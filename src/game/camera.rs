@@ -5,6 +5,8 @@ use leafwing_input_manager::{
 
 use crate::GameState;
 
+use super::OnGameScreen;
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
@@ -54,6 +56,7 @@ fn setup(mut commands: Commands) {
         Camera3d::default(),
         Transform::from_xyz(0.0, 7.5, 5.0).looking_at(Vec3::new(0.0, 4.0, 0.0), Vec3::Y),
         FollowCam,
+        OnGameScreen,
     ));
 
     // spawn 2D overlay
@@ -64,6 +67,7 @@ fn setup(mut commands: Commands) {
             clear_color: ClearColorConfig::None,
             ..Default::default()
         },
+        OnGameScreen,
     ));
 }
 
@@ -0,0 +1,288 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::GameState;
+
+use super::resolution::resolve_tower;
+use super::{
+    AllAssets, BaseElementType, DieFace, GamePlayState, GameResources, OwnedTower, Rarity,
+    TowerDetails,
+};
+
+// Money paid out for every pair of matching faces
+const PAIR_BONUS: usize = 10;
+// Money paid out per die when every die lands on the same element
+const FLUSH_BONUS: usize = 5;
+// Rarities in a row needed for a straight
+const STRAIGHT_LENGTH: usize = 3;
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ComboSummary>()
+            .add_systems(OnEnter(GamePlayState::Combo), evaluate_combos)
+            .add_systems(
+                Update,
+                combo_ui.run_if(in_state(GameState::Game).and(in_state(GamePlayState::Combo))),
+            );
+    }
+}
+
+// The combos found in the last rolling phase and what they paid out
+#[derive(Resource, Debug, Default)]
+struct ComboSummary(Vec<Combo>);
+
+#[derive(Debug, Clone, PartialEq)]
+struct Combo {
+    name: String,
+    bonus: ComboBonus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ComboBonus {
+    Money(usize),
+    // another tower like the one the face resolves into
+    ExtraTower(DieFace),
+    // the least rare tower waiting to be placed goes up a rarity
+    RarityBump,
+}
+
+impl std::fmt::Display for ComboBonus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComboBonus::Money(money) => write!(f, "+{} money", money),
+            ComboBonus::ExtraTower(face) => {
+                write!(f, "an extra {} {} tower", face.rarity, face)
+            }
+            ComboBonus::RarityBump => write!(f, "a tower goes up a rarity"),
+        }
+    }
+}
+
+/// Looks for combos among a rolling phase's results.
+///
+/// Faces of the same element and rarity make a pair or a triple, three or more rarities in a row
+/// make a straight, and three or more dice all landing on one element make a flush.
+fn find_combos(faces: &[DieFace]) -> Vec<Combo> {
+    let mut combos = Vec::new();
+
+    // in the order they were rolled, so the summary reads the same every time
+    let mut counted: Vec<DieFace> = Vec::new();
+    for face in faces {
+        if counted.contains(face) {
+            continue;
+        }
+        counted.push(*face);
+        match faces.iter().filter(|rolled| *rolled == face).count() {
+            1 => {}
+            2 => combos.push(Combo {
+                name: format!("Pair of {} {}", face.rarity, face),
+                bonus: ComboBonus::Money(PAIR_BONUS),
+            }),
+            count => combos.push(Combo {
+                name: format!("{} of {} {}", count, face.rarity, face),
+                bonus: ComboBonus::ExtraTower(*face),
+            }),
+        }
+    }
+
+    let mut run = 0;
+    let mut longest = 0;
+    for rarity in Rarity::ALL {
+        if faces.iter().any(|face| face.rarity == rarity) {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    if longest >= STRAIGHT_LENGTH {
+        combos.push(Combo {
+            name: format!("Straight of {} rarities", longest),
+            bonus: ComboBonus::RarityBump,
+        });
+    }
+
+    if let Some(first) = faces.first() {
+        let element = first.primary_type;
+        if faces.len() >= 3
+            && element != BaseElementType::None
+            && faces.iter().all(|face| face.primary_type == element)
+        {
+            combos.push(Combo {
+                name: format!("{} flush", element),
+                bonus: ComboBonus::Money(FLUSH_BONUS * faces.len()),
+            });
+        }
+    }
+
+    combos
+}
+
+// results are cleared whenever a rolling phase begins, so only this round's dice are counted
+fn evaluate_combos(
+    mut summary: ResMut<ComboSummary>,
+    mut game_resources: ResMut<GameResources>,
+    all_assets: Res<AllAssets>,
+    assets_towers: Res<Assets<TowerDetails>>,
+) {
+    let faces = game_resources
+        .dice
+        .iter()
+        .filter_map(|die| die.result)
+        .collect::<Vec<_>>();
+    summary.0 = find_combos(&faces);
+    for combo in summary.0.iter() {
+        info!("Combo: {} grants {}", combo.name, combo.bonus);
+        match combo.bonus {
+            ComboBonus::Money(money) => game_resources.money += money,
+            ComboBonus::ExtraTower(face) => {
+                let towers = all_assets.towers.iter().filter_map(|handle| {
+                    assets_towers
+                        .get(handle)
+                        .map(|tower_details| (handle.id(), tower_details))
+                });
+                if let Some(details) = resolve_tower(&face, towers) {
+                    game_resources.towers.push(OwnedTower {
+                        details,
+                        rarity: face.rarity,
+                        source_die: None,
+                    });
+                }
+            }
+            ComboBonus::RarityBump => {
+                if let Some(tower) = game_resources
+                    .towers
+                    .iter_mut()
+                    .filter(|tower| tower.rarity != Rarity::Unique)
+                    .min_by_key(|tower| tower.rarity)
+                {
+                    tower.rarity = tower.rarity.upgraded();
+                }
+            }
+        }
+    }
+}
+
+fn combo_ui(
+    mut contexts: EguiContexts,
+    summary: Res<ComboSummary>,
+    mut next_state: ResMut<NextState<GamePlayState>>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    egui::CentralPanel::default()
+        .frame(egui::Frame::none())
+        .show(ctx, |ui| {
+            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                ui.style_mut().spacing.item_spacing = egui::vec2(0.0, 10.0);
+
+                ui.add(egui::Label::new(egui::RichText::new("Combos").size(32.0)));
+
+                ui.add_space(10.0);
+
+                egui::Frame::dark_canvas(ui.style())
+                    .fill(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200))
+                    .show(ui, |ui| {
+                        if summary.0.is_empty() {
+                            ui.label(egui::RichText::new("No combos this round").size(18.0));
+                        }
+                        for combo in summary.0.iter() {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(&combo.name)
+                                        .size(18.0)
+                                        .color(egui::Color32::YELLOW),
+                                );
+                                ui.label(egui::RichText::new(combo.bonus.to_string()).size(18.0));
+                            });
+                        }
+                    });
+
+                if ui
+                    .add(
+                        egui::Button::new(egui::RichText::new("Continue to Placement").size(24.0))
+                            .min_size(egui::vec2(250.0, 40.0)),
+                    )
+                    .clicked()
+                {
+                    next_state.set(GamePlayState::Placement);
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(primary_type: BaseElementType, rarity: Rarity) -> DieFace {
+        DieFace {
+            primary_type,
+            secondary_type: None,
+            rarity,
+        }
+    }
+
+    #[test]
+    fn two_matching_faces_make_a_pair() {
+        let fire = face(BaseElementType::Fire, Rarity::Common);
+        let combos = find_combos(&[fire, face(BaseElementType::Water, Rarity::Uncommon), fire]);
+        assert_eq!(
+            combos,
+            vec![Combo {
+                name: "Pair of Common Fire".to_string(),
+                bonus: ComboBonus::Money(PAIR_BONUS),
+            }]
+        );
+    }
+
+    #[test]
+    fn three_matching_faces_make_a_triple_and_a_flush() {
+        let fire = face(BaseElementType::Fire, Rarity::Rare);
+        let combos = find_combos(&[fire, fire, fire]);
+        assert_eq!(
+            combos,
+            vec![
+                Combo {
+                    name: "3 of Rare Fire".to_string(),
+                    bonus: ComboBonus::ExtraTower(fire),
+                },
+                Combo {
+                    name: "Fire flush".to_string(),
+                    bonus: ComboBonus::Money(FLUSH_BONUS * 3),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rarities_in_a_row_make_a_straight() {
+        let combos = find_combos(&[
+            face(BaseElementType::Fire, Rarity::Common),
+            face(BaseElementType::Water, Rarity::Uncommon),
+            face(BaseElementType::Earth, Rarity::Rare),
+        ]);
+        assert_eq!(combos.len(), 1);
+        assert_eq!(combos[0].bonus, ComboBonus::RarityBump);
+    }
+
+    #[test]
+    fn unrelated_faces_make_no_combo() {
+        assert!(find_combos(&[]).is_empty());
+        assert!(find_combos(&[
+            face(BaseElementType::Fire, Rarity::Common),
+            face(BaseElementType::Water, Rarity::Rare),
+            face(BaseElementType::Earth, Rarity::Unique),
+        ])
+        .is_empty());
+        // dice with no element never make a flush
+        assert!(find_combos(&[
+            face(BaseElementType::None, Rarity::Common),
+            face(BaseElementType::None, Rarity::Rare),
+            face(BaseElementType::None, Rarity::Unique),
+        ])
+        .is_empty());
+    }
+}
@@ -1,23 +1,53 @@
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
 use std::f32::consts::PI;
 use std::time::Duration;
 
 use crate::despawn_screen;
 
-use super::{camera::FollowCam, Die, DieRollResultEvent, DieRolledEvent, GamePlayState};
+use rand::Rng;
+
+use super::{
+    camera::FollowCam, throw::Throw, BaseElementType, Die, DieFace, DieLoad, DieRollResultEvent,
+    DieRolledEvent, DieShape, GamePlayState, GameResources, Rarity, RunRng,
+};
+
+// Distance from the centre of a generated die to its corners, about the size of the d6 model
+const DIE_RADIUS: f32 = 0.5;
+// Golden ratio, the corners of the d12 and d20 sit on golden rectangles
+const PHI: f32 = 1.618_034;
+// Ballast of a loaded die, small and as far from the centre as still fits inside a d4, whose faces
+// are a third of the radius away
+const BALLAST_RADIUS: f32 = DIE_RADIUS * 0.04;
+const BALLAST_OFFSET: f32 = DIE_RADIUS * 0.28;
+// How far into a face marking its rarity reaches, the rest of the marking shows the element
+const MARKING_INSET: f32 = 0.5;
 
 pub struct DicePhysicsPlugin;
 
 impl Plugin for DicePhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ThrowPower(0.2))
+        app.init_resource::<CameraHome>()
             .add_systems(
                 Update,
-                (handle_dice_roll, check_dice_result, cleanup_dice)
+                (
+                    handle_dice_roll,
+                    check_dice_result,
+                    cleanup_dice,
+                    frame_dice,
+                )
                     .run_if(in_state(GamePlayState::Rolling)),
             )
-            .add_systems(OnExit(GamePlayState::Rolling), despawn_screen::<OnDieRoll>);
+            .add_systems(
+                OnExit(GamePlayState::Rolling),
+                (despawn_screen::<OnDieRoll>, restore_camera),
+            );
     }
 }
 
@@ -25,8 +55,18 @@ impl Plugin for DicePhysicsPlugin {
 #[derive(Component)]
 struct PhysicalDie {
     die_data: Die,
+    // the gesture it was thrown with, told along with the result
+    throw: Throw,
     is_rolling: bool,
     face_up: Option<usize>,
+    // results of a throw are only sent once every die thrown with it has settled
+    reported: bool,
+    // times the die came to rest cocked and was knocked over
+    nudges: u32,
+    // times the die was picked up and dropped again, after too many nudges or a timeout
+    rethrows: u32,
+    // times the die was set down flat, once it ran out of re-throws
+    settles: u32,
     roll_timeout_timer: Timer,
     roll_display_timer: Timer,
 }
@@ -35,73 +75,425 @@ struct PhysicalDie {
 #[derive(Component)]
 struct OnDieRoll;
 
-// Resource for tracking throw parameters
-#[derive(Resource)]
-pub struct ThrowPower(pub f32);
+// Where the camera was before it moved to frame the dice, to go back once they are gone
+#[derive(Resource, Default)]
+struct CameraHome(Option<Transform>);
+
+// Gap between dice thrown together, so they leave the hand side by side
+const DIE_SPACING: f32 = 0.6;
+// Nudges before a cocked die is thrown again
+const MAX_NUDGES: u32 = 3;
+// Re-throws before the die is set down flat on the face closest to up
+const MAX_RETHROWS: u32 = 2;
+// Times a die is set down flat before the face closest to up is taken, say when it is wedged
+// against a wall, so one stuck die cannot hold up the results of the others
+const MAX_SETTLES: u32 = 2;
+// Speeds below which a die counts as having stopped rolling, shared with the fairness simulator
+pub const SETTLED_LINEAR_SPEED: f32 = 0.1;
+pub const SETTLED_ANGULAR_SPEED: f32 = 0.1;
+
+fn tetrahedron() -> Vec<Vec3> {
+    vec![
+        Vec3::new(1.0, 1.0, 1.0),
+        Vec3::new(1.0, -1.0, -1.0),
+        Vec3::new(-1.0, 1.0, -1.0),
+        Vec3::new(-1.0, -1.0, 1.0),
+    ]
+}
+
+// ordered like the faces of the d6 model
+fn octahedron() -> Vec<Vec3> {
+    vec![
+        Vec3::X,
+        Vec3::NEG_X,
+        Vec3::Y,
+        Vec3::NEG_Y,
+        Vec3::Z,
+        Vec3::NEG_Z,
+    ]
+}
+
+fn cube() -> Vec<Vec3> {
+    let mut corners = Vec::new();
+    for x in [1.0, -1.0] {
+        for y in [1.0, -1.0] {
+            for z in [1.0, -1.0] {
+                corners.push(Vec3::new(x, y, z));
+            }
+        }
+    }
+    corners
+}
+
+fn icosahedron() -> Vec<Vec3> {
+    let mut corners = Vec::new();
+    for a in [1.0, -1.0] {
+        for b in [PHI, -PHI] {
+            corners.push(Vec3::new(0.0, a, b));
+            corners.push(Vec3::new(a, b, 0.0));
+            corners.push(Vec3::new(b, 0.0, a));
+        }
+    }
+    corners
+}
+
+fn dodecahedron() -> Vec<Vec3> {
+    let mut corners = cube();
+    for a in [PHI, -PHI] {
+        for b in [1.0 / PHI, -1.0 / PHI] {
+            corners.push(Vec3::new(0.0, a, b));
+            corners.push(Vec3::new(a, b, 0.0));
+            corners.push(Vec3::new(b, 0.0, a));
+        }
+    }
+    corners
+}
+
+impl DieShape {
+    /// Corners of the polyhedron, one unit away from its centre.
+    fn vertices(&self) -> Vec<Vec3> {
+        let corners = match self {
+            DieShape::D4 => tetrahedron(),
+            DieShape::D6 => cube(),
+            DieShape::D8 => octahedron(),
+            DieShape::D12 => dodecahedron(),
+            DieShape::D20 => icosahedron(),
+        };
+        corners.into_iter().map(Vec3::normalize).collect()
+    }
+
+    /// Outward normal of every face in die space, in the same order as [`Die::faces`].
+    ///
+    /// The faces of a polyhedron point at the corners of its dual.
+    fn face_normals(&self) -> Vec<Vec3> {
+        let normals = match self {
+            DieShape::D4 => tetrahedron().into_iter().map(|corner| -corner).collect(),
+            DieShape::D6 => octahedron(),
+            DieShape::D8 => cube(),
+            DieShape::D12 => icosahedron(),
+            DieShape::D20 => dodecahedron(),
+        };
+        normals.into_iter().map(Vec3::normalize).collect()
+    }
+
+    /// Direction in die space that points up when a face is the one rolled.
+    ///
+    /// A d4 lands on a face and is read from the corner left pointing up, so its result is the face
+    /// facing down. Every other die is read from the face on top.
+    pub fn result_directions(&self) -> Vec<Vec3> {
+        match self {
+            DieShape::D4 => self
+                .face_normals()
+                .into_iter()
+                .map(|normal| -normal)
+                .collect(),
+            _ => self.face_normals(),
+        }
+    }
+
+    /// Largest angle from straight up a result may lean at and still be read, a quarter of the
+    /// angle between two neighbouring results. A die leaning further is cocked.
+    pub fn settle_tolerance(&self) -> f32 {
+        let directions = self.result_directions();
+        let closest = directions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, a)| directions[i + 1..].iter().map(|b| a.angle_between(*b)))
+            .fold(PI, f32::min);
+        closest / 4.0
+    }
+
+    // the normal of every face with its corners, wound counter clockwise when looking at the face
+    // from outside
+    fn faces(&self) -> Vec<(Vec3, Vec<Vec3>)> {
+        let vertices = self.vertices();
+        self.face_normals()
+            .into_iter()
+            .map(|normal| {
+                let height = vertices
+                    .iter()
+                    .map(|vertex| vertex.dot(normal))
+                    .fold(f32::MIN, f32::max);
+                let mut corners: Vec<Vec3> = vertices
+                    .iter()
+                    .copied()
+                    .filter(|vertex| (vertex.dot(normal) - height).abs() < 1e-4)
+                    .collect();
+                let center = corners.iter().sum::<Vec3>() / corners.len() as f32;
+                let tangent = normal.any_orthonormal_vector();
+                let bitangent = normal.cross(tangent);
+                let angle = |corner: &Vec3| {
+                    let offset = *corner - center;
+                    offset.dot(bitangent).atan2(offset.dot(tangent))
+                };
+                corners.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+                (normal, corners)
+            })
+            .collect()
+    }
+
+    /// Volume of a generated die, a pyramid from the centre to every face.
+    fn volume(&self) -> f32 {
+        self.faces()
+            .iter()
+            .flat_map(|(_, corners)| {
+                (1..corners.len() - 1).map(move |i| {
+                    let [a, b, c] =
+                        [corners[0], corners[i], corners[i + 1]].map(|corner| corner * DIE_RADIUS);
+                    a.dot(b.cross(c)) / 6.0
+                })
+            })
+            .sum()
+    }
+
+    /// A plain flat shaded mesh of the polyhedron, see [`Self::marked_mesh`] for one that shows the
+    /// faces. Only the fairness simulator throws dice unmarked, so the web build goes without.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn mesh(&self) -> Mesh {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+        for (normal, corners) in self.faces() {
+            let start = positions.len() as u32;
+            for corner in corners.iter() {
+                positions.push(*corner * DIE_RADIUS);
+                normals.push(normal);
+            }
+            for i in 1..corners.len() as u32 - 1 {
+                indices.extend([start, start + i, start + i + 1]);
+            }
+        }
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+
+    /// A mesh of the polyhedron marked with what each face rolls, for dice without a model.
+    ///
+    /// Every face is painted in the colour of its element, with a patch in the middle in the colour
+    /// of its rarity. A dual element face alternates its two elements around the edge. A d4 is read
+    /// from the corner on top, so its markings sit in the corners instead, one per face touching
+    /// the corner. The markings lie flat on the faces, so the die rolls the same as [`Self::mesh`].
+    fn marked_mesh(&self, die_faces: &[DieFace]) -> Mesh {
+        let directions = self.result_directions();
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut colors = Vec::new();
+        let mut indices = Vec::new();
+        let mut push_polygon = |normal: Vec3, corners: &[Vec3], color: Color| {
+            let start = positions.len() as u32;
+            for corner in corners {
+                positions.push(*corner * DIE_RADIUS);
+                normals.push(normal);
+                colors.push(color.to_linear().to_f32_array());
+            }
+            for i in 1..corners.len() as u32 - 1 {
+                indices.extend([start, start + i, start + i + 1]);
+            }
+        };
+
+        for (i, (normal, corners)) in self.faces().into_iter().enumerate() {
+            let center = corners.iter().sum::<Vec3>() / corners.len() as f32;
+            // each marking with the point it is inset towards and the face it shows
+            let markings = match self {
+                DieShape::D4 => (0..corners.len())
+                    .map(|k| {
+                        let corner = corners[k];
+                        let next = corners[(k + 1) % corners.len()];
+                        let previous = corners[(k + corners.len() - 1) % corners.len()];
+                        // the result read when this corner points up
+                        let result = directions
+                            .iter()
+                            .map(|direction| direction.dot(corner))
+                            .enumerate()
+                            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                            .map(|(result, _)| result)
+                            .unwrap_or_default();
+                        (
+                            vec![
+                                corner,
+                                corner.lerp(next, 0.5),
+                                center,
+                                previous.lerp(corner, 0.5),
+                            ],
+                            corner,
+                            result,
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+                _ => vec![(corners, center, i)],
+            };
+
+            for (outline, anchor, result) in markings {
+                let Some(face) = die_faces.get(result) else {
+                    continue;
+                };
+                let inner = outline
+                    .iter()
+                    .map(|corner| anchor.lerp(*corner, MARKING_INSET))
+                    .collect::<Vec<_>>();
+                for k in 0..outline.len() {
+                    let next = (k + 1) % outline.len();
+                    let element = match face.secondary_type {
+                        Some(secondary) if k % 2 == 1 => secondary,
+                        _ => face.primary_type,
+                    };
+                    push_polygon(
+                        normal,
+                        &[outline[k], outline[next], inner[next], inner[k]],
+                        element_color(element),
+                    );
+                }
+                push_polygon(normal, &inner, rarity_color(face.rarity));
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+
+    /// A heavy sphere set inside a loaded die, under the face it favours so that face tends to end
+    /// up on top.
+    pub fn ballast(&self, load: &DieLoad) -> (Collider, ColliderDensity, Transform) {
+        let direction = self.result_directions()[load.face];
+        let ballast_volume = 4.0 / 3.0 * PI * BALLAST_RADIUS.powi(3);
+        (
+            Collider::sphere(BALLAST_RADIUS),
+            // colliders default to a density of one, so the die weighs as much as its volume
+            ColliderDensity(load.weight * self.volume() / ballast_volume),
+            Transform::from_translation(-direction * BALLAST_OFFSET),
+        )
+    }
+}
+
+fn element_color(element: BaseElementType) -> Color {
+    match element {
+        BaseElementType::None => Color::srgb(0.9, 0.9, 0.85),
+        BaseElementType::Fire => Color::srgb(0.9, 0.25, 0.1),
+        BaseElementType::Water => Color::srgb(0.15, 0.4, 0.9),
+        BaseElementType::Earth => Color::srgb(0.5, 0.35, 0.15),
+        BaseElementType::Wind => Color::srgb(0.6, 0.9, 0.6),
+    }
+}
+
+// the same colours the shop and forge list rarities in
+fn rarity_color(rarity: Rarity) -> Color {
+    match rarity {
+        Rarity::Common => Color::WHITE,
+        Rarity::Uncommon => Color::srgb(0.0, 1.0, 0.0),
+        Rarity::Rare => Color::srgb(0.0, 0.0, 1.0),
+        Rarity::Epic => Color::srgb(0.0, 0.0, 0.55),
+        Rarity::Unique => Color::srgb(1.0, 0.65, 0.0),
+    }
+}
 
-// Modified handle_dice_roll to use throw parameters
+// throws the die with the velocity and spin of the gesture it was thrown with
 fn handle_dice_roll(
     mut commands: Commands,
     mut ev_rolled: EventReader<DieRolledEvent>,
     camera_query: Query<&Transform, With<FollowCam>>,
-    throw_power: Res<ThrowPower>,
-    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<RunRng>,
+    mut game_resources: ResMut<GameResources>,
 ) {
     let transform = camera_query.single();
-    for ev in ev_rolled.read() {
-        let die_data = ev.0.clone();
-
-        // Calculate 3D throw direction from 2D input
-        let direction = Vec3::new(0.0, 0.5, -1.0).normalize();
+    let count = ev_rolled.len();
+    for (i, ev) in ev_rolled.read().enumerate() {
+        let Some(owned) = game_resources.dice.iter_mut().find(|die| die.id == ev.0) else {
+            warn!("Rolled a die that is not owned: {}", ev.0);
+            continue;
+        };
+        owned.rolling = true;
+        let die_data = owned.clone();
+        let shape = die_data.shape;
+        let faces = die_data.faces.clone();
+        let model = die_data.model.clone();
+        let load = die_data.load;
 
-        // Scale by power
-        let linear_velocity = direction * (throw_power.0 * 10.0 + 5.0); // Base power + scaling
+        let (linear_velocity, angular_velocity) = ev.1.launch(transform);
 
-        // Calculate a reasonable angular velocity
-        // should be spinning on a random axis
-        let spin_axis = Vec3::new(
-            rand::random::<f32>() - 0.5,
-            rand::random::<f32>() - 0.5,
-            rand::random::<f32>() - 0.5,
+        // a wobble on a random axis, so the same gesture does not always land the same face
+        let wobble_axis = Vec3::new(
+            rng.0.gen::<f32>() - 0.5,
+            rng.0.gen::<f32>() - 0.5,
+            rng.0.gen::<f32>() - 0.5,
         );
-        let angular_velocity = spin_axis * (throw_power.0 * PI * 4.0 + PI); // Base spin + scaling
+        let angular_velocity = angular_velocity + wobble_axis * PI;
 
         // Create the die with physics
-        commands.spawn((
-            SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset("models/die.glb"))),
+        let mut die = commands.spawn((
             Transform {
-                translation: transform.translation + transform.forward() * 2.0,
+                // dice thrown together are spread out along the camera's right
+                translation: Throw::origin(transform)
+                    + transform.right() * (i as f32 - (count - 1) as f32 / 2.0) * DIE_SPACING,
                 rotation: transform.rotation,
                 ..Default::default()
             },
             RigidBody::Dynamic,
-            ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
             LinearVelocity(linear_velocity),
             AngularVelocity(angular_velocity),
             PhysicalDie {
                 die_data,
+                throw: ev.1,
                 is_rolling: true,
                 face_up: None,
+                reported: false,
+                nudges: 0,
+                rethrows: 0,
+                settles: 0,
                 roll_timeout_timer: Timer::new(Duration::from_secs(5), TimerMode::Once),
                 roll_display_timer: Timer::new(Duration::from_secs(2), TimerMode::Once),
             },
             OnDieRoll,
         ));
+        // dice without a model are built from their shape's geometry
+        match model {
+            Some(scene) => die.insert((
+                SceneRoot(scene),
+                ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
+            )),
+            None => die.insert((
+                Mesh3d(meshes.add(shape.marked_mesh(&faces))),
+                // white so the markings keep their colours
+                MeshMaterial3d(materials.add(Color::WHITE)),
+                ColliderConstructor::ConvexHullFromMesh,
+            )),
+        };
+        if let Some(load) = load {
+            die.with_child(shape.ballast(&load));
+        }
     }
 }
 
 // System to check when the die has stopped rolling and determine the result
+// A die resting flat gives its result, one that is cocked is nudged over and one that timed out
+// or keeps landing cocked is thrown again. Out of re-throws it is set down flat on the face
+// closest to up, and if even that does not settle it, that face is taken as it lies
 fn check_dice_result(
     mut dice_query: Query<(
         &mut PhysicalDie,
-        &Transform,
-        &AngularVelocity,
-        &LinearVelocity,
+        &mut Transform,
+        &mut AngularVelocity,
+        &mut LinearVelocity,
     )>,
     mut ev_result: EventWriter<DieRollResultEvent>,
+    mut rng: ResMut<RunRng>,
     time: Res<Time>,
 ) {
-    for (mut physical_die, transform, ang_velocity, lin_velocity) in dice_query.iter_mut() {
+    for (mut physical_die, mut transform, mut ang_velocity, mut lin_velocity) in
+        dice_query.iter_mut()
+    {
         // Always tick the timer
         physical_die.roll_timeout_timer.tick(time.delta());
 
@@ -110,66 +502,114 @@ fn check_dice_result(
         }
 
         // Check if the die has stopped moving (almost) or timer expired
-        let linear_threshold = 0.1;
-        let angular_threshold = 0.1;
-
-        if (ang_velocity.length() < linear_threshold && lin_velocity.length() < angular_threshold)
-            || physical_die.roll_timeout_timer.finished()
-        {
-            // Die has stopped, determine which face is up
-            // Get the up vector in local dice space
-            let up = Vec3::Y;
+        let stopped = ang_velocity.length() < SETTLED_ANGULAR_SPEED
+            && lin_velocity.length() < SETTLED_LINEAR_SPEED;
+        let timed_out = physical_die.roll_timeout_timer.finished();
+        if !stopped && !timed_out {
+            continue;
+        }
 
-            // Determine which face is most aligned with up
-            let face_index = determine_face_up(transform, up);
+        // Die has stopped, determine which face is up and how flat it lies
+        let shape = physical_die.die_data.shape;
+        let directions = shape.result_directions();
+        let (face_index, alignment) = determine_face_up(transform.rotation, &directions);
+        let flat = alignment >= shape.settle_tolerance().cos();
 
-            physical_die.is_rolling = false;
-            physical_die.face_up = Some(face_index);
+        let random_axis = Vec3::new(
+            rng.0.gen::<f32>() - 0.5,
+            rng.0.gen::<f32>() - 0.5,
+            rng.0.gen::<f32>() - 0.5,
+        );
+        if stopped && !flat && physical_die.nudges < MAX_NUDGES {
+            info!(
+                "{} came to rest cocked, nudging it",
+                physical_die.die_data.name
+            );
+            physical_die.nudges += 1;
+            lin_velocity.0 += Vec3::Y * 1.5;
+            ang_velocity.0 += random_axis * PI * 2.0;
+            physical_die.roll_timeout_timer.reset();
+            continue;
+        }
+        if !(stopped && flat) && physical_die.rethrows < MAX_RETHROWS {
+            info!(
+                "{} {}, throwing it again",
+                physical_die.die_data.name,
+                if timed_out {
+                    "did not settle in time"
+                } else {
+                    "keeps landing cocked"
+                }
+            );
+            physical_die.rethrows += 1;
+            physical_die.nudges = 0;
+            // picked up where it lies and dropped from above with a fresh spin
+            transform.translation.y += 1.5;
+            lin_velocity.0 = Vec3::Y * 2.0;
+            ang_velocity.0 = random_axis * PI * 4.0;
+            physical_die.roll_timeout_timer.reset();
+            continue;
+        }
+        if !(stopped && flat) && physical_die.settles < MAX_SETTLES {
+            warn!(
+                "{} could not be read cleanly, setting it down on the face closest to up",
+                physical_die.die_data.name
+            );
+            physical_die.settles += 1;
+            // turned flat and let drop, it is read like any other die once it lands
+            let up = transform.rotation * directions[face_index];
+            transform.rotation = Quat::from_rotation_arc(up, Vec3::Y) * transform.rotation;
+            transform.translation.y += 0.2;
+            lin_velocity.0 = Vec3::ZERO;
+            ang_velocity.0 = Vec3::ZERO;
+            physical_die.roll_timeout_timer.reset();
+            continue;
+        }
+        if !(stopped && flat) {
+            warn!(
+                "{} still could not be read, taking the face closest to up",
+                physical_die.die_data.name
+            );
+        }
 
-            // Send the result event
-            let face = physical_die.die_data.faces[face_index];
-            ev_result.send(DieRollResultEvent(physical_die.die_data.clone(), face));
+        physical_die.is_rolling = false;
+        physical_die.face_up = Some(face_index);
+    }
 
-            physical_die.roll_display_timer.reset();
+    // every die in the air has to settle before any result is told
+    if dice_query.iter().any(|(die, ..)| die.is_rolling) {
+        return;
+    }
+    for (mut physical_die, ..) in dice_query.iter_mut() {
+        let Some(face_index) = physical_die.face_up else {
+            continue;
+        };
+        if physical_die.reported {
+            continue;
         }
+        // Send the result event
+        let face = physical_die.die_data.faces[face_index];
+        ev_result.send(DieRollResultEvent(
+            physical_die.die_data.id,
+            face,
+            face_index,
+            physical_die.throw,
+        ));
+
+        physical_die.reported = true;
+        physical_die.roll_display_timer.reset();
     }
 }
 
-// Helper function to determine which face is up based on the die's orientation
-fn determine_face_up(transform: &Transform, up: Vec3) -> usize {
-    // Get die local axes in world space
-    let local_x = transform.rotation * Vec3::X;
-    let local_y = transform.rotation * Vec3::Y;
-    let local_z = transform.rotation * Vec3::Z;
-
-    // Calculate dot products with world up vector
-    let dot_x = local_x.dot(up).abs();
-    let dot_y = local_y.dot(up).abs();
-    let dot_z = local_z.dot(up).abs();
-
-    // Find which axis is most aligned with up
-    if dot_x > dot_y && dot_x > dot_z {
-        // X axis is most aligned with up
-        if local_x.dot(up) > 0.0 {
-            0
-        } else {
-            1
-        }
-    } else if dot_y > dot_x && dot_y > dot_z {
-        // Y axis is most aligned with up
-        if local_y.dot(up) > 0.0 {
-            2
-        } else {
-            3
-        }
-    } else {
-        // Z axis is most aligned with up
-        if local_z.dot(up) > 0.0 {
-            4
-        } else {
-            5
-        }
-    }
+// Index of the die space direction that points closest to world up once rotated, and how close
+// it is: 1 when it points straight up
+pub fn determine_face_up(rotation: Quat, directions: &[Vec3]) -> (usize, f32) {
+    directions
+        .iter()
+        .map(|direction| (rotation * *direction).dot(Vec3::Y))
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap_or_default()
 }
 
 // Cleanup system for dice after roll is complete
@@ -180,8 +620,127 @@ fn cleanup_dice(
 ) {
     for (entity, mut die) in dice_query.iter_mut() {
         die.roll_display_timer.tick(time.delta());
-        if !die.is_rolling && die.roll_display_timer.just_finished() {
+        if die.reported && die.roll_display_timer.just_finished() {
             commands.entity(entity).despawn_recursive();
         }
     }
 }
+
+// moves the camera so every die on the table is in view, and back once they are cleaned up
+fn frame_dice(
+    time: Res<Time>,
+    mut home: ResMut<CameraHome>,
+    dice_query: Query<&Transform, (With<PhysicalDie>, Without<FollowCam>)>,
+    mut camera_query: Query<&mut Transform, With<FollowCam>>,
+) {
+    let mut camera = camera_query.single_mut();
+    let easing = 1.0 - (-4.0 * time.delta_secs()).exp();
+
+    if dice_query.is_empty() {
+        if let Some(home_transform) = home.0 {
+            camera.translation = camera.translation.lerp(home_transform.translation, easing);
+            camera.rotation = camera.rotation.slerp(home_transform.rotation, easing);
+            if camera.translation.distance(home_transform.translation) < 0.01 {
+                *camera = home_transform;
+                home.0 = None;
+            }
+        }
+        return;
+    }
+
+    let home_transform = *home.0.get_or_insert(*camera);
+    let count = dice_query.iter().count() as f32;
+    let center = dice_query.iter().map(|die| die.translation).sum::<Vec3>() / count;
+    let spread = dice_query
+        .iter()
+        .map(|die| die.translation.distance(center))
+        .fold(0.0, f32::max);
+
+    // looks at the group from the same side the camera was looking from, further back the wider it is
+    let distance = home_transform.translation.distance(center).min(6.0) + spread * 2.0;
+    let target = Transform::from_translation(center + home_transform.back() * distance)
+        .looking_at(center, Vec3::Y);
+    camera.translation = camera.translation.lerp(target.translation, easing);
+    camera.rotation = camera.rotation.slerp(target.rotation, easing);
+}
+
+fn restore_camera(
+    mut home: ResMut<CameraHome>,
+    mut camera_query: Query<&mut Transform, With<FollowCam>>,
+) {
+    // the camera is already gone when the run ends mid roll
+    if let (Some(home_transform), Ok(mut camera)) = (home.0.take(), camera_query.get_single_mut()) {
+        *camera = home_transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_normals_are_unit_length_and_distinct() {
+        for shape in DieShape::ALL {
+            let normals = shape.face_normals();
+            assert_eq!(normals.len(), shape.face_count(), "{}", shape);
+            for (i, normal) in normals.iter().enumerate() {
+                assert!(
+                    (normal.length() - 1.0).abs() < 1e-5,
+                    "{} face {}",
+                    shape,
+                    i + 1
+                );
+                for other in normals[i + 1..].iter() {
+                    assert!(
+                        normal.angle_between(*other) > 0.1,
+                        "{} face {}",
+                        shape,
+                        i + 1
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_face_reads_when_resting_flat() {
+        for shape in DieShape::ALL {
+            let directions = shape.result_directions();
+            let tolerance = shape.settle_tolerance().cos();
+            for (i, normal) in shape.face_normals().into_iter().enumerate() {
+                // lying on the face, for a d4 that puts the result on the table
+                let rotation = Quat::from_rotation_arc(normal, Vec3::NEG_Y);
+                let (_, alignment) = determine_face_up(rotation, &directions);
+                assert!(alignment >= tolerance, "{} face {}", shape, i + 1);
+
+                let rotation = Quat::from_rotation_arc(directions[i], Vec3::Y);
+                let (face, alignment) = determine_face_up(rotation, &directions);
+                assert_eq!(face, i, "{}", shape);
+                assert!(alignment >= tolerance, "{} face {}", shape, i + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn leaning_past_the_tolerance_is_cocked() {
+        for shape in DieShape::ALL {
+            let directions = shape.result_directions();
+            let tolerance = shape.settle_tolerance();
+            for (i, direction) in directions.iter().enumerate() {
+                let upright = Quat::from_rotation_arc(*direction, Vec3::Y);
+                for (lean, flat) in [(tolerance * 0.5, true), (tolerance * 1.5, false)] {
+                    let rotation = Quat::from_rotation_x(lean) * upright;
+                    let (face, alignment) = determine_face_up(rotation, &directions);
+                    assert_eq!(face, i, "{}", shape);
+                    assert_eq!(
+                        alignment >= tolerance.cos(),
+                        flat,
+                        "{} face {}",
+                        shape,
+                        i + 1
+                    );
+                }
+            }
+        }
+    }
+}
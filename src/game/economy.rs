@@ -3,28 +3,25 @@ use bevy_egui::{egui, EguiContexts};
 
 use crate::GameState;
 
+use super::forge::ForgePlugin;
 use super::{
-    BaseElementType, Die, DieBuilder, DiePurchaseEvent, GamePlayState, GameResources, Rarity,
+    seed_run, AllAssets, Die, DieBuilder, DiePurchaseEvent, DieTemplate, GamePlayState,
+    GameResources, Rarity, RunRng,
 };
 
+// Price of a reroll to spend in the next rolling phase
+const REROLL_PRICE: usize = 15;
+
 pub struct EconomyPlugin;
 
 impl Plugin for EconomyPlugin {
     fn build(&self, app: &mut App) {
-        let shop_items = vec![
-            DieBuilder::from_d6_type(BaseElementType::Fire).build(),
-            DieBuilder::from_d6_type(BaseElementType::Water).build(),
-            DieBuilder::from_d6_type(BaseElementType::Earth).build(),
-            DieBuilder::from_d6_type(BaseElementType::Wind).build(),
-        ];
-        app.insert_resource(DieShop {
-            highlighted: 0,
-            items: shop_items,
-        })
-        .add_systems(
-            Update,
-            economy_ui.run_if(in_state(GamePlayState::Economy).and(in_state(GameState::Game))),
-        );
+        app.add_plugins(ForgePlugin)
+            .add_systems(OnEnter(GameState::Game), stock_shop.after(seed_run))
+            .add_systems(
+                Update,
+                economy_ui.run_if(in_state(GamePlayState::Economy).and(in_state(GameState::Game))),
+            );
     }
 }
 
@@ -34,12 +31,35 @@ struct DieShop {
     highlighted: usize,
 }
 
+// the shop is drawn from the run's rng so a seed always offers the same dice
+fn stock_shop(
+    mut commands: Commands,
+    mut rng: ResMut<RunRng>,
+    all_assets: Res<AllAssets>,
+    assets_dice: Res<Assets<DieTemplate>>,
+) {
+    let items = all_assets
+        .dice
+        .iter()
+        .filter_map(|handle| assets_dice.get(handle))
+        .map(|template| DieBuilder::from_template(template).build(&mut rng.0))
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        warn!("No dice defined, the shop is empty");
+    }
+    commands.insert_resource(DieShop {
+        highlighted: 0,
+        items,
+    });
+}
+
 fn economy_ui(
     mut contexts: EguiContexts,
     mut shop: ResMut<DieShop>,
     mut economy: ResMut<GameResources>,
     mut ev_die_purchase: EventWriter<DiePurchaseEvent>,
     mut next_state: ResMut<NextState<GamePlayState>>,
+    mut rng: ResMut<RunRng>,
 ) {
     let ctx = contexts.ctx_mut();
 
@@ -67,82 +87,117 @@ fn economy_ui(
 
                 ui.add_space(10.0);
 
-                ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                    // Display die info in a frame
-                    egui::Frame::dark_canvas(ui.style())
-                        .fill(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200))
-                        .show(ui, |ui| {
-                            ui.label(
-                                egui::RichText::new(format!("Die #{}", shop.highlighted + 1))
-                                    .size(18.0),
-                            );
-                            // Navigation and selection row
-                            ui.horizontal(|ui| {
-                                // Left button
-                                if ui.button(egui::RichText::new("◀").size(24.0)).clicked() {
-                                    shop.highlighted = (shop.highlighted + shop.items.len() - 1)
-                                        % shop.items.len();
-                                }
-                                // Right button
-                                if ui.button(egui::RichText::new("▶").size(24.0)).clicked() {
-                                    shop.highlighted = (shop.highlighted + 1) % shop.items.len();
-                                }
-                            });
-                            let current_die = &shop.items[shop.highlighted];
-
-                            ui.label(format!("Cost: {}", current_die.value));
-
-                            ui.separator();
-
-                            // Show die faces
-                            ui.label("Faces:");
-                            for (i, face) in current_die.faces.iter().enumerate() {
-                                let color = match face.rarity {
-                                    Rarity::Common => egui::Color32::WHITE,
-                                    Rarity::Uncommon => egui::Color32::GREEN,
-                                    Rarity::Rare => egui::Color32::BLUE,
-                                    Rarity::Epic => egui::Color32::DARK_BLUE,
-                                    Rarity::Unique => egui::Color32::ORANGE,
-                                };
-
+                // the way on stays open even with nothing to buy
+                if shop.items.is_empty() {
+                    ui.label(egui::RichText::new("No dice for sale").size(18.0));
+                } else {
+                    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                        // Display die info in a frame
+                        egui::Frame::dark_canvas(ui.style())
+                            .fill(egui::Color32::from_rgba_premultiplied(0, 0, 0, 200))
+                            .show(ui, |ui| {
                                 ui.label(
                                     egui::RichText::new(format!(
-                                        "{}. {}",
-                                        i + 1,
-                                        face.primary_type
+                                        "{} ({})",
+                                        shop.items[shop.highlighted].name,
+                                        shop.items[shop.highlighted].shape
                                     ))
-                                    .color(color),
+                                    .size(18.0),
                                 );
-                            }
+                                // Navigation and selection row
+                                ui.horizontal(|ui| {
+                                    // Left button
+                                    if ui.button(egui::RichText::new("◀").size(24.0)).clicked() {
+                                        shop.highlighted = (shop.highlighted + shop.items.len()
+                                            - 1)
+                                            % shop.items.len();
+                                    }
+                                    // Right button
+                                    if ui.button(egui::RichText::new("▶").size(24.0)).clicked() {
+                                        shop.highlighted =
+                                            (shop.highlighted + 1) % shop.items.len();
+                                    }
+                                });
+                                let current_die = &shop.items[shop.highlighted];
+
+                                ui.label(format!("Cost: {}", current_die.value));
+
+                                ui.separator();
+
+                                // Show die faces
+                                ui.label("Faces:");
+                                for (i, face) in current_die.faces.iter().enumerate() {
+                                    let color = match face.rarity {
+                                        Rarity::Common => egui::Color32::WHITE,
+                                        Rarity::Uncommon => egui::Color32::GREEN,
+                                        Rarity::Rare => egui::Color32::BLUE,
+                                        Rarity::Epic => egui::Color32::DARK_BLUE,
+                                        Rarity::Unique => egui::Color32::ORANGE,
+                                    };
+
+                                    // the face a loaded die's ballast sits under
+                                    let loaded =
+                                        current_die.load.is_some_and(|load| load.face == i);
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "{}. {}{}",
+                                            i + 1,
+                                            face,
+                                            if loaded { " (loaded)" } else { "" }
+                                        ))
+                                        .color(color),
+                                    );
+                                }
+                                if let Some(load) = current_die.load {
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "Loaded, ballast weighs {:.0}% of the die",
+                                            load.weight * 100.0
+                                        ))
+                                        .color(egui::Color32::YELLOW),
+                                    );
+                                }
 
-                            ui.separator();
+                                ui.separator();
 
-                            // Purchase button
-                            let already_purchased = economy.dice.contains(current_die);
-                            let can_purchase =
-                                economy.money >= current_die.value && !already_purchased;
+                                // Purchase button, the same die can be bought more than once
+                                let can_purchase = economy.money >= current_die.value;
 
-                            if ui
-                                .add_enabled(can_purchase, egui::Button::new("Purchase"))
-                                .clicked()
-                            {
-                                economy.money -= current_die.value;
-                                ev_die_purchase.send(DiePurchaseEvent(current_die.clone()));
-                            }
+                                if ui
+                                    .add_enabled(can_purchase, egui::Button::new("Purchase"))
+                                    .clicked()
+                                {
+                                    economy.money -= current_die.value;
+                                    ev_die_purchase
+                                        .send(DiePurchaseEvent(current_die.duplicate(&mut rng.0)));
+                                }
 
-                            if already_purchased {
-                                ui.label(
-                                    egui::RichText::new("Already Purchased")
-                                        .color(egui::Color32::YELLOW),
-                                );
-                            } else if !can_purchase {
-                                ui.label(
-                                    egui::RichText::new("Not enough money")
-                                        .color(egui::Color32::RED),
-                                );
-                            }
-                        });
-                });
+                                if !can_purchase {
+                                    ui.label(
+                                        egui::RichText::new("Not enough money")
+                                            .color(egui::Color32::RED),
+                                    );
+                                }
+                            });
+                    });
+                }
+
+                if ui
+                    .add_enabled(
+                        economy.money >= REROLL_PRICE,
+                        egui::Button::new(format!("Buy Reroll ({})", REROLL_PRICE)),
+                    )
+                    .clicked()
+                {
+                    economy.money -= REROLL_PRICE;
+                    economy.bought_rerolls += 1;
+                }
+                if economy.bought_rerolls > 0 {
+                    ui.label(format!(
+                        "Extra rerolls next round: {}",
+                        economy.bought_rerolls
+                    ));
+                }
 
                 if ui
                     .add_enabled(
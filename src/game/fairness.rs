@@ -0,0 +1,479 @@
+use avian3d::prelude::*;
+use bevy::{
+    app::PluginsState,
+    asset::AssetMetaCheck,
+    prelude::*,
+    render::{settings::WgpuSettings, RenderPlugin},
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::f32::consts::{PI, TAU};
+use std::f64::consts::SQRT_2;
+use std::time::Duration;
+
+use super::{
+    dice_physics::{determine_face_up, SETTLED_ANGULAR_SPEED, SETTLED_LINEAR_SPEED},
+    throw::Throw,
+    DieLoad, DieShape,
+};
+
+// Simulated time per frame, exactly one physics step at avian's default 64Hz
+const FRAME: Duration = Duration::from_micros(15_625);
+// Frames a die gets to settle, the same five seconds the game waits before re-throwing
+const MAX_FRAMES: u32 = 320;
+// Significance below which a die is reported as biased
+const SIGNIFICANCE: f64 = 0.05;
+
+/// What the fairness simulator throws, and how often.
+#[derive(Debug, Clone)]
+pub struct FairnessConfig {
+    pub shape: DieShape,
+    pub throws: u32,
+    pub seed: u64,
+    /// Scene to take the collider from instead of the shape's own geometry, e.g.
+    /// `models/die.glb`. Loading it needs the asset pipeline, so the simulator then runs on the
+    /// default plugins with the window and renderer switched off.
+    pub model: Option<String>,
+    /// Ballast to throw the die with, to see how far it tips the odds.
+    pub load: Option<DieLoad>,
+}
+
+impl Default for FairnessConfig {
+    fn default() -> Self {
+        FairnessConfig {
+            shape: DieShape::D6,
+            throws: 1000,
+            seed: 0,
+            model: None,
+            load: None,
+        }
+    }
+}
+
+impl FairnessConfig {
+    /// Read from `--simulate-dice <shape> [--throws <n>] [--seed <n>] [--model <path>]
+    /// [--load-face <n> [--load-weight <w>]]`, `None` when the game was started without
+    /// `--simulate-dice`.
+    pub fn from_args() -> Option<Self> {
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        if !args.iter().any(|arg| arg == "--simulate-dice") {
+            return None;
+        }
+        let value = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|i| args.get(i + 1).cloned())
+        };
+
+        let shape = value("--simulate-dice").unwrap_or_default();
+        let mut config = FairnessConfig::default();
+        match DieShape::ALL.into_iter().find(|s| s.to_string() == shape) {
+            Some(shape) => config.shape = shape,
+            None => eprintln!("--simulate-dice expects one of d4, d6, d8, d12 or d20, using a d6"),
+        }
+        if let Some(throws) = value("--throws") {
+            match throws.parse() {
+                Ok(throws) => config.throws = throws,
+                Err(_) => eprintln!("--throws expects a number, using {}", config.throws),
+            }
+        }
+        if let Some(seed) = value("--seed") {
+            match seed.parse() {
+                Ok(seed) => config.seed = seed,
+                Err(_) => eprintln!("--seed expects a number, using {}", config.seed),
+            }
+        }
+        config.model = value("--model");
+        if let Some(face) = value("--load-face") {
+            let weight = value("--load-weight").unwrap_or_else(|| "1.0".to_string());
+            match (face.parse::<usize>(), weight.parse()) {
+                (Ok(face), Ok(weight)) if (1..=config.shape.face_count()).contains(&face) => {
+                    config.load = Some(DieLoad {
+                        face: face - 1,
+                        weight,
+                    })
+                }
+                _ => eprintln!(
+                    "--load-face expects a face of the {} and --load-weight a number, throwing it fair",
+                    config.shape
+                ),
+            }
+        }
+        Some(config)
+    }
+}
+
+/// How often each face of a die came up over a simulated session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FairnessReport {
+    pub shape: DieShape,
+    /// Throws that came to rest flat on each face, in the same order as [`super::Die::faces`].
+    pub counts: Vec<u32>,
+    /// Throws that came to rest leaning too far to be read.
+    pub cocked: u32,
+    /// Throws that were still moving when the game would have given up on them.
+    pub timed_out: u32,
+}
+
+impl FairnessReport {
+    fn new(shape: DieShape) -> Self {
+        FairnessReport {
+            shape,
+            counts: vec![0; shape.face_count()],
+            cocked: 0,
+            timed_out: 0,
+        }
+    }
+
+    /// Throws that gave a result.
+    pub fn read(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    pub fn throws(&self) -> u32 {
+        self.read() + self.cocked + self.timed_out
+    }
+
+    /// Pearson's chi-squared statistic of the counts against every face being equally likely.
+    pub fn chi_squared(&self) -> f64 {
+        let read = self.read();
+        if read == 0 {
+            return 0.0;
+        }
+        let expected = read as f64 / self.counts.len() as f64;
+        self.counts
+            .iter()
+            .map(|count| (*count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    /// Chance of a fair die scattering at least this far from even, using the Wilson–Hilferty
+    /// approximation of the chi-squared distribution.
+    pub fn p_value(&self) -> f64 {
+        let k = (self.counts.len() - 1) as f64;
+        let spread = 2.0 / (9.0 * k);
+        let z = ((self.chi_squared() / k).cbrt() - (1.0 - spread)) / spread.sqrt();
+        1.0 - normal_cdf(z)
+    }
+
+    pub fn is_fair(&self, significance: f64) -> bool {
+        self.p_value() >= significance
+    }
+}
+
+impl std::fmt::Display for FairnessReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let read = self.read().max(1) as f64;
+        writeln!(f, "{} thrown {} times", self.shape, self.throws())?;
+        writeln!(f, "face  count  share")?;
+        for (i, count) in self.counts.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:>5}  {:>5.1}%",
+                i + 1,
+                count,
+                *count as f64 / read * 100.0
+            )?;
+        }
+        writeln!(f, "cocked: {}, timed out: {}", self.cocked, self.timed_out)?;
+        writeln!(
+            f,
+            "chi-squared {:.2} with {} degrees of freedom, p = {:.4}",
+            self.chi_squared(),
+            self.counts.len() - 1,
+            self.p_value()
+        )?;
+        if self.is_fair(SIGNIFICANCE) {
+            write!(f, "no sign of bias at {SIGNIFICANCE}")
+        } else {
+            write!(f, "biased at {SIGNIFICANCE}")
+        }
+    }
+}
+
+// Abramowitz and Stegun 7.1.26, good to about 1e-7
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let polynomial = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - polynomial * (-x * x).exp();
+    0.5 * (1.0 + erf.copysign(z))
+}
+
+/// Throws a die over and over without a window and counts the faces it lands on.
+///
+/// Every throw leaves the hand like one from the game, with a random gesture and a random
+/// starting orientation, and is read with the same [`determine_face_up`] the game uses.
+pub fn simulate_fairness(config: &FairnessConfig) -> FairnessReport {
+    let mut app = App::new();
+    match config.model {
+        None => app
+            .add_plugins((
+                MinimalPlugins,
+                TransformPlugin,
+                HierarchyPlugin,
+                AssetPlugin::default(),
+                // avian looks for the scene spawner to build colliders from scenes
+                bevy::scene::ScenePlugin,
+            ))
+            .init_asset::<Mesh>(),
+        Some(_) => app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+                .set(AssetPlugin {
+                    meta_check: AssetMetaCheck::Never,
+                    ..default()
+                })
+                .disable::<WinitPlugin>(),
+        ),
+    };
+    app.add_plugins(PhysicsPlugins::default())
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+        .insert_resource(Simulation {
+            config: config.clone(),
+            rng: StdRng::seed_from_u64(config.seed),
+            report: FairnessReport::new(config.shape),
+            model: None,
+            die: None,
+        })
+        .add_systems(Startup, setup_table)
+        .add_systems(Update, (throw_die, read_die).chain());
+
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    while app.world().resource::<Simulation>().report.throws() < config.throws {
+        app.update();
+    }
+    app.world().resource::<Simulation>().report.clone()
+}
+
+#[derive(Resource)]
+struct Simulation {
+    config: FairnessConfig,
+    rng: StdRng,
+    report: FairnessReport,
+    model: Option<Handle<Scene>>,
+    // the die in the air and the frames it has been rolling for
+    die: Option<(Entity, u32)>,
+}
+
+#[derive(Component)]
+struct SimulatedDie;
+
+fn setup_table(
+    mut commands: Commands,
+    mut simulation: ResMut<Simulation>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((RigidBody::Static, Collider::half_space(Vec3::Y)));
+
+    if let Some(path) = simulation.config.model.clone() {
+        simulation.model = Some(asset_server.load(GltfAssetLabel::Scene(0).from_asset(path)));
+    }
+}
+
+fn throw_die(
+    mut commands: Commands,
+    mut simulation: ResMut<Simulation>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asset_server: Res<AssetServer>,
+) {
+    if simulation.die.is_some() {
+        return;
+    }
+    if let Some(model) = &simulation.model {
+        if !asset_server.is_loaded_with_dependencies(model) {
+            return;
+        }
+    }
+    let model = simulation.model.clone();
+    let shape = simulation.config.shape;
+    let load = simulation.config.load;
+    let rng = &mut simulation.rng;
+
+    // a camera looking down at the table like the game's, any gesture and any way up
+    let camera = Transform::from_xyz(0.0, 3.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y);
+    let throw = Throw {
+        aim: Vec2::from_angle(rng.gen::<f32>() * TAU),
+        power: rng.gen_range(0.1..=1.0),
+        curl: rng.gen_range(-1.0..=1.0),
+    };
+    let (linear_velocity, angular_velocity) = throw.launch(&camera);
+    let wobble_axis = Vec3::new(
+        rng.gen::<f32>() - 0.5,
+        rng.gen::<f32>() - 0.5,
+        rng.gen::<f32>() - 0.5,
+    );
+
+    let mut die = commands.spawn((
+        Transform::from_translation(Throw::origin(&camera)).with_rotation(random_rotation(rng)),
+        RigidBody::Dynamic,
+        LinearVelocity(linear_velocity),
+        AngularVelocity(angular_velocity + wobble_axis * PI),
+        SimulatedDie,
+    ));
+    // the same colliders the game builds for the die
+    match model {
+        Some(scene) => die.insert((
+            SceneRoot(scene),
+            ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
+        )),
+        None => die.insert((
+            Mesh3d(meshes.add(shape.mesh())),
+            ColliderConstructor::ConvexHullFromMesh,
+        )),
+    };
+    if let Some(load) = load {
+        die.with_child(shape.ballast(&load));
+    }
+    simulation.die = Some((die.id(), 0));
+}
+
+// Shoemake's uniformly distributed rotation
+fn random_rotation(rng: &mut impl Rng) -> Quat {
+    let (u, a, b) = (
+        rng.gen::<f32>(),
+        rng.gen::<f32>() * TAU,
+        rng.gen::<f32>() * TAU,
+    );
+    let (s, t) = ((1.0 - u).sqrt(), u.sqrt());
+    Quat::from_xyzw(s * a.sin(), s * a.cos(), t * b.sin(), t * b.cos())
+}
+
+fn read_die(
+    mut commands: Commands,
+    mut simulation: ResMut<Simulation>,
+    dice_query: Query<(&Transform, &LinearVelocity, &AngularVelocity), With<SimulatedDie>>,
+) {
+    let Some((entity, frames)) = simulation.die else {
+        return;
+    };
+    let Ok((transform, lin_velocity, ang_velocity)) = dice_query.get(entity) else {
+        return;
+    };
+
+    // the die needs a step to pick up its throw before it can count as stopped
+    let stopped = frames > 0
+        && lin_velocity.length() < SETTLED_LINEAR_SPEED
+        && ang_velocity.length() < SETTLED_ANGULAR_SPEED;
+    if !stopped && frames < MAX_FRAMES {
+        simulation.die = Some((entity, frames + 1));
+        return;
+    }
+
+    let shape = simulation.config.shape;
+    let (face_index, alignment) = determine_face_up(transform.rotation, &shape.result_directions());
+    let report = &mut simulation.report;
+    if !stopped {
+        report.timed_out += 1;
+    } else if alignment < shape.settle_tolerance().cos() {
+        report.cocked += 1;
+    } else {
+        report.counts[face_index] += 1;
+    }
+    commands.entity(entity).despawn_recursive();
+    simulation.die = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(counts: Vec<u32>) -> FairnessReport {
+        FairnessReport {
+            shape: DieShape::D6,
+            counts,
+            cocked: 0,
+            timed_out: 0,
+        }
+    }
+
+    #[test]
+    fn chi_squared_of_even_and_uneven_counts() {
+        assert_eq!(report(vec![10; 6]).chi_squared(), 0.0);
+        assert_eq!(report(vec![0; 6]).chi_squared(), 0.0);
+        // 60 throws expect 10 of each, two faces 5 off make (25 + 25) / 10
+        assert!((report(vec![5, 15, 10, 10, 10, 10]).chi_squared() - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn normal_cdf_matches_the_table() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-4);
+        assert!((normal_cdf(-1.96) - 0.025).abs() < 1e-4);
+        assert!((normal_cdf(-3.0) - 0.001_35).abs() < 1e-5);
+    }
+
+    #[test]
+    fn p_value_is_close_to_the_exact_distribution() {
+        // chi-squared 11.26 with 5 degrees of freedom, the exact tail is 0.0465
+        let biased = report(vec![123, 77, 105, 95, 103, 97]);
+        assert!((biased.chi_squared() - 11.26).abs() < 1e-9);
+        assert!((biased.p_value() - 0.0465).abs() < 0.002);
+        assert!(!biased.is_fair(SIGNIFICANCE));
+
+        let even = report(vec![100; 6]);
+        assert!(even.p_value() > 0.99);
+        assert!(even.is_fair(SIGNIFICANCE));
+    }
+
+    #[test]
+    fn simulation_throws_as_often_as_asked() {
+        let config = FairnessConfig {
+            throws: 20,
+            seed: 1,
+            ..default()
+        };
+        let report = simulate_fairness(&config);
+        assert_eq!(report.throws(), 20);
+        assert_eq!(report.counts.len(), 6);
+        assert!(report.read() >= 10, "{}", report);
+    }
+
+    #[test]
+    fn loaded_die_favours_its_face_and_a_fair_one_does_not() {
+        let fair = simulate_fairness(&FairnessConfig {
+            throws: 300,
+            seed: 7,
+            ..default()
+        });
+        // a fixed seed throws the same way every time, the loose significance keeps the check
+        // about the die rather than the seed
+        assert!(fair.is_fair(0.01), "{}", fair);
+
+        // a heavy load, so the bias stands out from what the seed alone throws
+        let loaded = simulate_fairness(&FairnessConfig {
+            throws: 300,
+            seed: 7,
+            load: Some(DieLoad {
+                face: 0,
+                weight: 4.0,
+            }),
+            ..default()
+        });
+        let share = loaded.counts[0] as f64 / loaded.read() as f64;
+        assert!(share > 1.5 / 6.0, "{}", loaded);
+        assert!(!loaded.is_fair(SIGNIFICANCE), "{}", loaded);
+    }
+}
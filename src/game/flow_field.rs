@@ -0,0 +1,195 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+use vleue_navigator::prelude::*;
+
+use crate::GameState;
+
+use super::{Goal, NAVMESH_BOUNDS};
+
+// Width of a flow field cell, half a tower tile so enemies can squeeze between towers
+const CELL_SIZE: f32 = 0.5;
+// Dijkstra step costs, scaled by ten to stay in integers
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+pub struct FlowFieldPlugin;
+
+impl Plugin for FlowFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowField>()
+            .add_systems(Update, build_flow_field.run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Distance to the [`Goal`] of every cell of a grid laid over the navmesh.
+///
+/// It is rebuilt once per navmesh rebuild and shared by every enemy, which steers towards its
+/// cheapest neighbouring cell instead of planning a path of its own.
+#[derive(Resource, Default)]
+pub struct FlowField {
+    // world position (x, z) of the corner of the first cell
+    origin: Vec2,
+    width: usize,
+    height: usize,
+    walkable: Vec<bool>,
+    // step cost to the goal, u32::MAX when the goal cannot be reached
+    costs: Vec<u32>,
+    goal: Vec3,
+}
+
+impl FlowField {
+    /// Lays the grid over a navmesh and floods it with the distance to the goal.
+    pub fn build(navmesh: &NavMesh, navmesh_transform: &Transform, goal: Vec3) -> Self {
+        let corners =
+            NAVMESH_BOUNDS.map(|corner| navmesh_transform.transform_point(corner.extend(0.0)).xz());
+        let min = corners
+            .iter()
+            .fold(Vec2::MAX, |min, corner| min.min(*corner));
+        let max = corners
+            .iter()
+            .fold(Vec2::MIN, |max, corner| max.max(*corner));
+        let size = ((max - min) / CELL_SIZE).ceil();
+
+        let mut field = FlowField {
+            origin: min,
+            width: size.x as usize,
+            height: size.y as usize,
+            walkable: Vec::new(),
+            costs: Vec::new(),
+            goal,
+        };
+        let cells = field.width * field.height;
+        field.walkable = (0..cells)
+            .map(|index| {
+                let center = field.center(index);
+                navmesh.transformed_is_in_mesh(Vec3::new(center.x, 0.0, center.y))
+            })
+            .collect();
+        field.costs = vec![u32::MAX; cells];
+
+        let Some(goal_cell) = field.cell_at(goal) else {
+            warn!("Goal is outside of the navmesh");
+            return field;
+        };
+        let mut queue = BinaryHeap::new();
+        field.costs[goal_cell] = 0;
+        queue.push(Reverse((0, goal_cell)));
+        while let Some(Reverse((cost, index))) = queue.pop() {
+            if cost > field.costs[index] {
+                continue;
+            }
+            // collected first, the neighbours borrow the field the costs are written to
+            let neighbours = field.neighbours(index).collect::<Vec<_>>();
+            for (neighbour, step) in neighbours {
+                if cost + step < field.costs[neighbour] {
+                    field.costs[neighbour] = cost + step;
+                    queue.push(Reverse((cost + step, neighbour)));
+                }
+            }
+        }
+        field
+    }
+
+    fn cell_at(&self, position: Vec3) -> Option<usize> {
+        let cell = ((position.xz() - self.origin) / CELL_SIZE).floor();
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
+        }
+        let (x, y) = (cell.x as usize, cell.y as usize);
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    fn center(&self, index: usize) -> Vec2 {
+        let x = (index % self.width) as f32;
+        let y = (index / self.width) as f32;
+        self.origin + (Vec2::new(x, y) + 0.5) * CELL_SIZE
+    }
+
+    // walkable neighbours with the cost of stepping to them; diagonals may not cut corners
+    fn neighbours(&self, index: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        let x = (index % self.width) as isize;
+        let y = (index / self.width) as isize;
+        let cell = move |dx: isize, dy: isize| {
+            let (nx, ny) = (x + dx, y + dy);
+            (nx >= 0 && ny >= 0 && (nx as usize) < self.width && (ny as usize) < self.height)
+                .then(|| ny as usize * self.width + nx as usize)
+                .filter(|neighbour| self.walkable[*neighbour])
+        };
+        [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ]
+        .into_iter()
+        .filter_map(move |(dx, dy)| {
+            let neighbour = cell(dx, dy)?;
+            if dx != 0 && dy != 0 {
+                cell(dx, 0)?;
+                cell(0, dy)?;
+                Some((neighbour, DIAGONAL_COST))
+            } else {
+                Some((neighbour, STRAIGHT_COST))
+            }
+        })
+    }
+
+    /// Direction to walk in from a position, `None` once there is nowhere better to go.
+    pub fn direction(&self, position: Vec3) -> Option<Vec3> {
+        let index = self.cell_at(position)?;
+        if self.costs[index] == 0 {
+            return (self.goal - position).with_y(0.0).try_normalize();
+        }
+        let (best, _) = self
+            .neighbours(index)
+            .filter(|(neighbour, _)| self.costs[*neighbour] != u32::MAX)
+            .min_by_key(|(neighbour, _)| self.costs[*neighbour])?;
+        let target = self.center(best);
+        (Vec3::new(target.x, position.y, target.y) - position)
+            .with_y(0.0)
+            .try_normalize()
+    }
+
+    /// Whether the goal can be walked to from a position without leaving the grid's walkable
+    /// cells.
+    pub fn reaches_goal(&self, position: Vec3) -> bool {
+        self.cell_at(position)
+            .is_some_and(|index| self.costs[index] != u32::MAX)
+    }
+
+    /// Approximate walking distance from a position to the goal.
+    pub fn distance_to_goal(&self, position: Vec3) -> f32 {
+        match self.cell_at(position).map(|index| self.costs[index]) {
+            Some(cost) if cost != u32::MAX => cost as f32 / STRAIGHT_COST as f32 * CELL_SIZE,
+            _ => f32::MAX,
+        }
+    }
+}
+
+fn build_flow_field(
+    mut flow_field: ResMut<FlowField>,
+    navmeshes: Res<Assets<NavMesh>>,
+    navmesh_query: Query<(&ManagedNavMesh, Ref<NavMeshStatus>, &Transform)>,
+    goal_query: Query<&Transform, With<Goal>>,
+) {
+    let Ok((navmesh_handle, status, navmesh_transform)) = navmesh_query.get_single() else {
+        return;
+    };
+    if *status != NavMeshStatus::Built || !status.is_changed() {
+        return;
+    }
+    let (Some(navmesh), Ok(goal)) = (navmeshes.get(navmesh_handle), goal_query.get_single()) else {
+        return;
+    };
+    *flow_field = FlowField::build(navmesh, navmesh_transform, goal.translation);
+    info!(
+        "Built flow field of {}x{} cells",
+        flow_field.width, flow_field.height
+    );
+}
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::GameState;
+
+use super::{BaseElementType, GamePlayState, GameResources, Rarity};
+
+pub struct ForgePlugin;
+
+impl Plugin for ForgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            forge_ui.run_if(in_state(GamePlayState::Economy).and(in_state(GameState::Game))),
+        );
+    }
+}
+
+// the face being worked on and what it is being worked into
+struct ForgeSelection {
+    face: usize,
+    element: BaseElementType,
+    // the face a copy of the selected face is forged onto
+    target: usize,
+}
+
+impl Default for ForgeSelection {
+    fn default() -> Self {
+        ForgeSelection {
+            face: 0,
+            // a face can only be reforged into one of the elements
+            element: BaseElementType::Fire,
+            target: 0,
+        }
+    }
+}
+
+enum ForgeWork {
+    Upgrade,
+    Reforge(BaseElementType),
+    Duplicate(usize),
+}
+
+// Rework the faces of an owned die, what is spent on a die adds to its value
+fn forge_ui(
+    mut contexts: EguiContexts,
+    mut game_resources: ResMut<GameResources>,
+    mut selection: Local<ForgeSelection>,
+) {
+    if game_resources.dice.is_empty() {
+        return;
+    }
+    let ctx = contexts.ctx_mut();
+
+    let GameResources {
+        money,
+        dice,
+        highlighted_die,
+        ..
+    } = &mut *game_resources;
+    *highlighted_die = (*highlighted_die).min(dice.len() - 1);
+
+    egui::Window::new("Forge")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("◀").clicked() {
+                    *highlighted_die = (*highlighted_die + dice.len() - 1) % dice.len();
+                }
+                ui.label(format!(
+                    "{} ({})",
+                    dice[*highlighted_die].name, dice[*highlighted_die].shape
+                ));
+                if ui.button("▶").clicked() {
+                    *highlighted_die = (*highlighted_die + 1) % dice.len();
+                }
+            });
+            let die = &mut dice[*highlighted_die];
+            selection.face = selection.face.min(die.faces.len() - 1);
+            selection.target = selection.target.min(die.faces.len() - 1);
+
+            ui.label(format!("Value: {}", die.value));
+            ui.separator();
+
+            for (i, face) in die.faces.iter().enumerate() {
+                let color = match face.rarity {
+                    Rarity::Common => egui::Color32::WHITE,
+                    Rarity::Uncommon => egui::Color32::GREEN,
+                    Rarity::Rare => egui::Color32::BLUE,
+                    Rarity::Epic => egui::Color32::DARK_BLUE,
+                    Rarity::Unique => egui::Color32::ORANGE,
+                };
+                ui.selectable_value(
+                    &mut selection.face,
+                    i,
+                    egui::RichText::new(format!("{}. {} ({})", i + 1, face, face.rarity))
+                        .color(color),
+                );
+            }
+
+            ui.separator();
+
+            let face = die.faces[selection.face];
+            let price = face.rarity.forge_price();
+            let mut work = None;
+
+            let can_upgrade = face.rarity != Rarity::Unique && *money >= price;
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        can_upgrade,
+                        egui::Button::new(format!(
+                            "Upgrade to {} ({})",
+                            face.rarity.upgraded(),
+                            price
+                        )),
+                    )
+                    .clicked()
+                {
+                    work = Some((ForgeWork::Upgrade, price));
+                }
+                if face.rarity != Rarity::Unique {
+                    not_enough_money(ui, *money, price);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("forge_element")
+                    .selected_text(selection.element.to_string())
+                    .show_ui(ui, |ui| {
+                        for element in BaseElementType::ALL
+                            .into_iter()
+                            .filter(|element| *element != BaseElementType::None)
+                        {
+                            ui.selectable_value(
+                                &mut selection.element,
+                                element,
+                                element.to_string(),
+                            );
+                        }
+                    });
+                // changing the element is half the work of raising the rarity
+                let reforge_price = price / 2;
+                let can_reforge = face.primary_type != selection.element && *money >= reforge_price;
+                if ui
+                    .add_enabled(
+                        can_reforge,
+                        egui::Button::new(format!("Reforge ({})", reforge_price)),
+                    )
+                    .clicked()
+                {
+                    work = Some((ForgeWork::Reforge(selection.element), reforge_price));
+                }
+                not_enough_money(ui, *money, reforge_price);
+            });
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("forge_target")
+                    .selected_text(format!("Onto face {}", selection.target + 1))
+                    .show_ui(ui, |ui| {
+                        for i in 0..die.faces.len() {
+                            ui.selectable_value(
+                                &mut selection.target,
+                                i,
+                                format!("Onto face {}", i + 1),
+                            );
+                        }
+                    });
+                let can_duplicate = selection.target != selection.face
+                    && die.faces[selection.target] != face
+                    && *money >= price;
+                if ui
+                    .add_enabled(
+                        can_duplicate,
+                        egui::Button::new(format!("Duplicate ({})", price)),
+                    )
+                    .clicked()
+                {
+                    work = Some((ForgeWork::Duplicate(selection.target), price));
+                }
+                not_enough_money(ui, *money, price);
+            });
+
+            if let Some((work, price)) = work {
+                match work {
+                    ForgeWork::Upgrade => {
+                        die.faces[selection.face].rarity = face.rarity.upgraded();
+                    }
+                    ForgeWork::Reforge(element) => {
+                        let face = &mut die.faces[selection.face];
+                        face.primary_type = element;
+                        // a face cannot carry the same element twice
+                        if face.secondary_type == Some(element) {
+                            face.secondary_type = None;
+                        }
+                    }
+                    ForgeWork::Duplicate(target) => {
+                        die.faces[target] = face;
+                    }
+                }
+                *money -= price;
+                die.value += price;
+            }
+        });
+}
+
+// next to the work it is about, each kind of work costs its own price
+fn not_enough_money(ui: &mut egui::Ui, money: usize, price: usize) {
+    if money < price {
+        ui.label(egui::RichText::new("Not enough money").color(egui::Color32::RED));
+    }
+}
@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use uuid::Uuid;
+
+use crate::GameState;
+
+use super::{DieFace, DieRollResultEvent, GameResources, RunSeed};
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RollHistory>()
+            .add_systems(OnEnter(GameState::Game), clear_history)
+            .add_systems(
+                Update,
+                (record_rolls, history_ui).run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+// A die coming to rest, as it was rolled
+#[derive(Debug, Clone, PartialEq)]
+struct RollRecord {
+    // the wave the roll was for, counting from one
+    round: usize,
+    die: Uuid,
+    die_name: String,
+    face_index: usize,
+    face: DieFace,
+    // 0..=1, how hard the die was thrown
+    power: f32,
+}
+
+/// Every roll of the run, oldest first, kept after the dice are cleaned up off the table.
+#[derive(Resource, Debug, Default)]
+struct RollHistory(Vec<RollRecord>);
+
+impl RollHistory {
+    // one roll per line under a header, for balance analysis in a spreadsheet
+    fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("round,die_id,die_name,face,element,secondary_element,rarity,power\n");
+        for record in self.0.iter() {
+            csv.push_str(&format!(
+                "{},{},\"{}\",{},{},{},{},{:.2}\n",
+                record.round,
+                record.die,
+                record.die_name.replace('"', "\"\""),
+                record.face_index + 1,
+                record.face.primary_type,
+                record
+                    .face
+                    .secondary_type
+                    .map(|element| element.to_string())
+                    .unwrap_or_default(),
+                record.face.rarity,
+                record.power
+            ));
+        }
+        csv
+    }
+
+    // how often each face of a die has come up
+    fn face_counts(&self, die: Uuid, faces: usize) -> Vec<u32> {
+        let mut counts = vec![0; faces];
+        for record in self.0.iter().filter(|record| record.die == die) {
+            if let Some(count) = counts.get_mut(record.face_index) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+}
+
+fn clear_history(mut history: ResMut<RollHistory>) {
+    history.0.clear();
+}
+
+fn record_rolls(
+    mut history: ResMut<RollHistory>,
+    mut ev_result: EventReader<DieRollResultEvent>,
+    game_resources: Res<GameResources>,
+) {
+    for ev in ev_result.read() {
+        let die_name = game_resources
+            .dice
+            .iter()
+            .find(|die| die.id == ev.0)
+            .map(|die| die.name.clone())
+            .unwrap_or_default();
+        history.0.push(RollRecord {
+            round: game_resources.wave + 1,
+            die: ev.0,
+            die_name,
+            face_index: ev.2,
+            face: ev.1,
+            power: ev.3.power,
+        });
+    }
+}
+
+fn history_ui(
+    mut contexts: EguiContexts,
+    history: Res<RollHistory>,
+    game_resources: Res<GameResources>,
+    seed: Res<RunSeed>,
+    mut export_status: Local<Option<String>>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    egui::Window::new("Roll History")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if history.0.is_empty() {
+                ui.label("No rolls yet");
+                return;
+            }
+
+            // the web build has no files to write, the csv goes to the clipboard there instead
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Export CSV").clicked() {
+                let path = format!("roll_history_{}.csv", seed.0);
+                *export_status = Some(match std::fs::write(&path, history.to_csv()) {
+                    Ok(()) => {
+                        info!("Exported roll history to {}", path);
+                        format!("Saved to {}", path)
+                    }
+                    Err(err) => {
+                        warn!("Could not export roll history to {}: {}", path, err);
+                        format!("Could not save: {}", err)
+                    }
+                });
+            }
+            #[cfg(target_arch = "wasm32")]
+            if ui.button("Copy CSV").clicked() {
+                ui.ctx().copy_text(history.to_csv());
+                *export_status = Some(format!("Copied the rolls of seed {}", seed.0));
+            }
+            if let Some(status) = export_status.as_ref() {
+                ui.label(status);
+            }
+
+            egui::CollapsingHeader::new("Rolls")
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            // newest first
+                            for record in history.0.iter().rev() {
+                                ui.label(format!(
+                                    "Round {}: {} face {}, {} {} at {:.0}% power",
+                                    record.round,
+                                    record.die_name,
+                                    record.face_index + 1,
+                                    record.face.rarity,
+                                    record.face,
+                                    record.power * 100.0
+                                ));
+                            }
+                        });
+                });
+
+            egui::CollapsingHeader::new("Face frequencies").show(ui, |ui| {
+                for die in game_resources.dice.iter() {
+                    let counts = history.face_counts(die.id, die.faces.len());
+                    let total = counts.iter().sum::<u32>();
+                    if total == 0 {
+                        continue;
+                    }
+                    ui.label(
+                        egui::RichText::new(format!("{} ({} rolls)", die.name, total)).strong(),
+                    );
+                    ui.label(
+                        counts
+                            .iter()
+                            .enumerate()
+                            .map(|(i, count)| format!("{}: {}", i + 1, count))
+                            .collect::<Vec<_>>()
+                            .join("  "),
+                    );
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{BaseElementType, Rarity};
+
+    fn record(die: Uuid, die_name: &str, face_index: usize, face: DieFace) -> RollRecord {
+        RollRecord {
+            round: 1,
+            die,
+            die_name: die_name.to_string(),
+            face_index,
+            face,
+            power: 0.5,
+        }
+    }
+
+    #[test]
+    fn csv_has_a_header_and_one_escaped_line_per_roll() {
+        let die = Uuid::nil();
+        let history = RollHistory(vec![
+            record(
+                die,
+                "The \"Lucky\" d6",
+                0,
+                DieFace {
+                    primary_type: BaseElementType::Fire,
+                    secondary_type: None,
+                    rarity: Rarity::Common,
+                },
+            ),
+            record(
+                die,
+                "Steam, d8",
+                7,
+                DieFace {
+                    primary_type: BaseElementType::Fire,
+                    secondary_type: Some(BaseElementType::Water),
+                    rarity: Rarity::Rare,
+                },
+            ),
+        ]);
+        assert_eq!(
+            history.to_csv(),
+            format!(
+                "round,die_id,die_name,face,element,secondary_element,rarity,power\n\
+                 1,{die},\"The \"\"Lucky\"\" d6\",1,Fire,,Common,0.50\n\
+                 1,{die},\"Steam, d8\",8,Fire,Water,Rare,0.50\n"
+            )
+        );
+    }
+
+    #[test]
+    fn face_counts_only_count_the_asked_die() {
+        let die = Uuid::from_u128(1);
+        let other = Uuid::from_u128(2);
+        let face = DieFace {
+            primary_type: BaseElementType::Earth,
+            secondary_type: None,
+            rarity: Rarity::Common,
+        };
+        let history = RollHistory(vec![
+            record(die, "d4", 0, face),
+            record(die, "d4", 2, face),
+            record(die, "d4", 2, face),
+            record(other, "d6", 1, face),
+        ]);
+        assert_eq!(history.face_counts(die, 4), vec![1, 0, 2, 0]);
+        assert_eq!(history.face_counts(other, 6), vec![0, 1, 0, 0, 0, 0]);
+        assert_eq!(history.face_counts(Uuid::from_u128(3), 4), vec![0; 4]);
+    }
+}
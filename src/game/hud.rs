@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{GameResources, OnGameScreen};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Game), setup)
+            .add_systems(
+                Update,
+                update_hud.run_if(in_state(GameState::Game).and(resource_changed::<GameResources>)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct HudText;
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        Text::default(),
+        HudText,
+        OnGameScreen,
+    ));
+}
+
+fn update_hud(game_resources: Res<GameResources>, mut query: Query<&mut Text, With<HudText>>) {
+    for mut text in query.iter_mut() {
+        text.0 = format!(
+            "Lives: {}\nWave: {}",
+            game_resources.lives,
+            game_resources.wave + 1
+        );
+    }
+}
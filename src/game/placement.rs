@@ -1,9 +1,19 @@
-use bevy::{gltf::GltfMesh, prelude::*};
+use bevy::{
+    gltf::GltfMesh,
+    prelude::*,
+    render::{mesh::MeshAabb, primitives::Aabb},
+};
+use bevy_egui::{egui, EguiContexts};
 use leafwing_input_manager::{prelude::*, Actionlike, InputControlKind};
+use vleue_navigator::prelude::*;
 
 use crate::{despawn_screen, GameState};
 
-use super::{BaseElementType, GamePlayState, GameResources, TowerDetails, Wave, SNAP_OFFSET};
+use super::flow_field::FlowField;
+use super::{
+    BaseElementType, ElementChart, GamePlayState, GameResources, Goal, Obstacle, Rarity,
+    SpawnPoint, TowerDetails, NAVMESH_BOUNDS, SNAP_OFFSET,
+};
 
 pub struct PlacementPlugin;
 
@@ -12,6 +22,7 @@ impl Plugin for PlacementPlugin {
         app.add_plugins(InputManagerPlugin::<PlacementAction>::default())
             .init_resource::<ActionState<PlacementAction>>()
             .insert_resource(PlacementAction::default_input_map())
+            .init_resource::<PlacementCheck>()
             .add_systems(OnEnter(GamePlayState::Placement), setup)
             .add_systems(
                 Update,
@@ -19,16 +30,26 @@ impl Plugin for PlacementPlugin {
                     control_cursor,
                     placeholder_snap_to_cursor,
                     display_placeholder,
+                    tint_placeholder.after(display_placeholder),
                     toggle_placeholder_type,
-                    // place_tower,
+                    check_placement
+                        .after(placeholder_snap_to_cursor)
+                        .after(display_placeholder),
+                    place_tower.after(check_placement),
+                    refresh_tower_list.run_if(resource_changed::<GameResources>),
                     update_tower_selection,
+                    inspect_tower,
                     start_wave,
                 )
                     .run_if(in_state(GameState::Game).and(in_state(GamePlayState::Placement))),
             )
             .add_systems(
                 OnExit(GamePlayState::Placement),
-                despawn_screen::<PlacementOverlay>,
+                (
+                    despawn_screen::<PlacementOverlay>,
+                    despawn_screen::<TowerPlaceholder>,
+                    despawn_screen::<CursorPlaceholder>,
+                ),
             );
     }
 }
@@ -78,7 +99,52 @@ impl PlacementAction {
 pub struct Tower {
     pub name: String,
     pub element_type: BaseElementType,
+    // hybrid towers hit with both elements
+    pub secondary_element: Option<BaseElementType>,
+    pub rarity: Rarity,
+    pub range: f32,
+    pub damage: u32,
+    pub projectile_speed: f32,
+    pub projectile_mesh: Handle<Mesh>,
+    pub projectile_material: Handle<StandardMaterial>,
+    // ready to fire once finished, reset on every shot
     pub attack_speed: Timer,
+    pub targeting: TargetingMode,
+}
+
+/// Which enemy in range a tower shoots at.
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TargetingMode {
+    /// The enemy furthest along its way to the goal.
+    #[default]
+    First,
+    /// The enemy furthest from the goal.
+    Last,
+    Strongest,
+    Weakest,
+    Closest,
+}
+
+impl TargetingMode {
+    const ALL: [TargetingMode; 5] = [
+        TargetingMode::First,
+        TargetingMode::Last,
+        TargetingMode::Strongest,
+        TargetingMode::Weakest,
+        TargetingMode::Closest,
+    ];
+}
+
+impl std::fmt::Display for TargetingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetingMode::First => write!(f, "First"),
+            TargetingMode::Last => write!(f, "Last"),
+            TargetingMode::Strongest => write!(f, "Strongest"),
+            TargetingMode::Weakest => write!(f, "Weakest"),
+            TargetingMode::Closest => write!(f, "Closest"),
+        }
+    }
 }
 
 #[derive(Reflect, Component)]
@@ -86,6 +152,8 @@ pub struct Tower {
 pub struct Projectile {
     pub speed: f32,
     pub damage: u32,
+    pub element_type: BaseElementType,
+    pub secondary_element: Option<BaseElementType>,
     pub target: Entity,
     pub lifetime: Timer,
 }
@@ -101,9 +169,25 @@ pub struct CursorPlaceholder;
 #[derive(Reflect, Component)]
 pub struct PlacementOverlay;
 
+// Index into the tower pool of an entry in the placement overlay
+#[derive(Component)]
+struct TowerListItem(usize);
+
+// Result of validating the tile under the tower placeholder, cached until the tile changes.
+#[derive(Resource, Default)]
+struct PlacementCheck {
+    tile: Option<Vec2>,
+    valid: bool,
+}
+
+#[derive(Resource)]
+struct PlacementMaterials {
+    invalid: Handle<StandardMaterial>,
+}
+
 fn setup(
     mut commands: Commands,
-    game_resources: ResMut<GameResources>,
+    game_resources: Res<GameResources>,
     mut assets_mesh: ResMut<Assets<Mesh>>,
     assets_gltfmesh: Res<Assets<GltfMesh>>,
     assets_towers: Res<Assets<TowerDetails>>,
@@ -114,7 +198,7 @@ fn setup(
         .towers
         .get(game_resources.highlighted_tower)
         .unwrap();
-    let tower_details = assets_towers.get(*tower).unwrap();
+    let tower_details = assets_towers.get(tower.details).unwrap();
     let gltf = res.get(&tower_details.model).unwrap();
     let mesh = assets_gltfmesh.get(&gltf.meshes[0]).unwrap();
     let mesh3d = mesh.primitives[0].mesh.clone();
@@ -126,6 +210,14 @@ fn setup(
         TowerPlaceholder,
     ));
 
+    commands.insert_resource(PlacementMaterials {
+        invalid: materials.add(StandardMaterial {
+            base_color: Color::srgba(1.0, 0.0, 0.0, 0.6),
+            alpha_mode: AlphaMode::Blend,
+            ..Default::default()
+        }),
+    });
+
     let blue = materials.add(StandardMaterial {
         base_color: Color::srgb(0.0, 0.0, 1.0),
         ..Default::default()
@@ -138,6 +230,14 @@ fn setup(
         CursorPlaceholder,
     ));
 
+    spawn_tower_list(&mut commands, &game_resources, &assets_towers);
+}
+
+fn spawn_tower_list(
+    commands: &mut Commands,
+    game_resources: &GameResources,
+    assets_towers: &Assets<TowerDetails>,
+) {
     let mut p = commands.spawn((
         Node {
             align_items: AlignItems::Center,
@@ -157,9 +257,8 @@ fn setup(
         Text::new("Towers:"),
     ));
 
-    for tower in game_resources.towers.iter() {
-        let tower_details = assets_towers.get(*tower).unwrap();
-        let idx = game_resources.highlighted_tower;
+    for (i, tower) in game_resources.towers.iter().enumerate() {
+        let tower_details = assets_towers.get(tower.details).unwrap();
         p.with_child((
             Node {
                 width: Val::Percent(20.),
@@ -168,17 +267,13 @@ fn setup(
                 justify_content: JustifyContent::Center,
                 ..default()
             },
-            BackgroundColor(if tower == &game_resources.towers[idx] {
+            BackgroundColor(if i == game_resources.highlighted_tower {
                 Color::srgba(0., 0., 0., 0.5)
             } else {
                 Color::srgba(0., 0., 0., 0.8)
             }),
-            TowerDetails {
-                name: tower_details.name.clone(),
-                element_type: tower_details.element_type,
-                model: tower_details.model.clone(),
-            },
-            Text::new(tower_details.name.clone()),
+            TowerListItem(i),
+            Text::new(format!("{} ({})", tower_details.name, tower.rarity)),
         ));
     }
 }
@@ -211,20 +306,28 @@ fn placeholder_snap_to_cursor(
     placeholder_query
         .iter_mut()
         .for_each(|mut placeholder_transform| {
-            let snap_distance = 1.0;
-            let snap_x = (cursor_position.x - SNAP_OFFSET / snap_distance).round() + SNAP_OFFSET;
-            let snap_z = (cursor_position.z - SNAP_OFFSET / snap_distance).round() + SNAP_OFFSET;
+            let snapped = snap_to_tile(cursor_position);
 
-            placeholder_transform.translation.x = snap_x;
-            placeholder_transform.translation.z = snap_z;
+            placeholder_transform.translation.x = snapped.x;
+            placeholder_transform.translation.z = snapped.y;
         });
 }
 
+// centre of the grid tile under a position, on the xz plane
+fn snap_to_tile(position: Vec3) -> Vec2 {
+    let snap_distance = 1.0;
+    let snap_x = (position.x - SNAP_OFFSET / snap_distance).round() + SNAP_OFFSET;
+    let snap_z = (position.z - SNAP_OFFSET / snap_distance).round() + SNAP_OFFSET;
+    Vec2::new(snap_x, snap_z)
+}
+
 fn toggle_placeholder_type(
     action_state: Res<ActionState<PlacementAction>>,
     mut game_resources: ResMut<GameResources>,
 ) {
-    if action_state.just_pressed(&PlacementAction::ToggleTowerType) {
+    if action_state.just_pressed(&PlacementAction::ToggleTowerType)
+        && !game_resources.towers.is_empty()
+    {
         game_resources.highlighted_tower =
             (game_resources.highlighted_tower + 1) % game_resources.towers.len();
     }
@@ -232,18 +335,13 @@ fn toggle_placeholder_type(
 
 fn update_tower_selection(
     game_resources: Res<GameResources>,
-    assets_towers: Res<Assets<TowerDetails>>,
-    mut query: Query<(&mut BackgroundColor, &TowerDetails)>,
+    mut query: Query<(&mut BackgroundColor, &TowerListItem)>,
 ) {
     for (mut bg_color, item) in query.iter_mut() {
-        let idx = game_resources.highlighted_tower;
-        if let Some(tower) = game_resources.towers.get(idx) {
-            let tower_details = assets_towers.get(*tower).unwrap();
-            if item == tower_details {
-                *bg_color = BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5));
-            } else {
-                *bg_color = BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8));
-            }
+        if item.0 == game_resources.highlighted_tower {
+            *bg_color = BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5));
+        } else {
+            *bg_color = BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8));
         }
     }
 }
@@ -267,7 +365,7 @@ fn display_placeholder(
     }
     let idx = game_resources.highlighted_tower;
     if let Some(tower) = game_resources.towers.get(idx) {
-        let tower_details = assets_towers.get(*tower).unwrap();
+        let tower_details = assets_towers.get(tower.details).unwrap();
         let gltf = res.get(&tower_details.model).unwrap();
         let mesh = assets_gltfmesh.get(&gltf.meshes[0]).unwrap();
         query.iter_mut().for_each(|(mut mesh3d, mut mat, _)| {
@@ -277,48 +375,237 @@ fn display_placeholder(
     }
 }
 
-// fn place_tower(
-//     action_state: Res<ActionState<PlacementAction>>,
-//     mut commands: Commands,
-//     assets_towers: Res<Assets<TowerDetails>>,
-//     res: Res<Assets<Gltf>>,
-//     assets_gltfmesh: Res<Assets<GltfMesh>>,
-//     mut tower_pool: ResMut<TowerPool>,
-//     placeholder_query: Query<&Transform, With<TowerPlaceholder>>,
-// ) {
-//     if action_state.just_pressed(&PlacementAction::PlaceTower) {
-//         let placeholder_transform = placeholder_query.single();
-//         if let Some(tower) = tower_pool.get_highlighted() {
-//             let tower_details = assets_towers.get(*tower).unwrap();
-//             let gltf = res.get(&tower_details.model).unwrap();
-//             let mesh = assets_gltfmesh.get(&gltf.meshes[0]).unwrap();
-//             let mesh3d = mesh.primitives[0].mesh.clone();
-//             let mat = gltf.materials[0].clone();
-//             commands.spawn((
-//                 Mesh3d(mesh3d),
-//                 Transform::from_translation(placeholder_transform.translation),
-//                 MeshMaterial3d(mat),
-//                 Tower {
-//                     name: tower_details.name.clone(),
-//                     element_type: tower_details.element_type,
-//                     attack_speed: Timer::from_seconds(1.0, TimerMode::Repeating),
-//                 },
-//                 Obstacle,
-//             ));
-//         }
-//         tower_pool.remove_tower(tower_pool.get_highlighted().unwrap());
-//     }
-// }
+// rebuilds the tower list whenever the pool changes, e.g. after a tower is placed
+fn refresh_tower_list(
+    mut commands: Commands,
+    game_resources: Res<GameResources>,
+    assets_towers: Res<Assets<TowerDetails>>,
+    overlay_query: Query<Entity, With<PlacementOverlay>>,
+) {
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_tower_list(&mut commands, &game_resources, &assets_towers);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn place_tower(
+    action_state: Res<ActionState<PlacementAction>>,
+    mut commands: Commands,
+    mut game_resources: ResMut<GameResources>,
+    assets_towers: Res<Assets<TowerDetails>>,
+    res: Res<Assets<Gltf>>,
+    assets_gltfmesh: Res<Assets<GltfMesh>>,
+    placeholder_query: Query<&Transform, With<TowerPlaceholder>>,
+    mut check: ResMut<PlacementCheck>,
+) {
+    if !action_state.just_pressed(&PlacementAction::PlaceTower) {
+        return;
+    }
+    let Ok(placeholder_transform) = placeholder_query.get_single() else {
+        return;
+    };
+    if check.tile != Some(placeholder_transform.translation.xz()) || !check.valid {
+        info!("Tile is occupied or would block the path to the goal");
+        return;
+    }
+    let idx = game_resources.highlighted_tower;
+    if idx >= game_resources.towers.len() {
+        return;
+    }
+
+    let tower = game_resources.towers.remove(idx);
+    if game_resources.highlighted_tower >= game_resources.towers.len() {
+        game_resources.highlighted_tower = 0;
+    }
+
+    let tower_details = assets_towers.get(tower.details).unwrap();
+    let gltf = res.get(&tower_details.model).unwrap();
+    let mesh = assets_gltfmesh.get(&gltf.meshes[0]).unwrap();
+    let mesh3d = mesh.primitives[0].mesh.clone();
+    let mat = gltf.materials[0].clone();
+    let stats = tower_details.stats.scaled(tower.rarity);
+    commands.spawn((
+        Mesh3d(mesh3d),
+        Transform::from_translation(placeholder_transform.translation),
+        MeshMaterial3d(mat),
+        Tower {
+            name: tower_details.name.clone(),
+            element_type: tower_details.element_type,
+            secondary_element: tower_details.secondary_element,
+            rarity: tower.rarity,
+            range: stats.range,
+            damage: stats.damage,
+            projectile_speed: stats.projectile_speed,
+            projectile_mesh: tower_details.projectile_mesh.clone(),
+            projectile_material: tower_details.projectile_material.clone(),
+            attack_speed: Timer::from_seconds(1.0 / stats.fire_rate, TimerMode::Once),
+            targeting: TargetingMode::default(),
+        },
+        Obstacle,
+    ));
+    // the new tower changes the navmesh, so the tile has to be validated again
+    check.tile = None;
+}
+
+// validates the tile under the placeholder: it must be free, and a tower there must still leave
+// a way from every spawn point to the goal. The way is checked on the flow field enemies steer
+// by, a gap the navmesh squeezes through can still be too narrow for any of its cells
+fn check_placement(
+    mut check: ResMut<PlacementCheck>,
+    meshes: Res<Assets<Mesh>>,
+    placeholder_query: Query<(&Transform, &Mesh3d), With<TowerPlaceholder>>,
+    navmesh_query: Query<&Transform, With<NavMeshSettings>>,
+    obstacle_query: Query<(&Aabb, &GlobalTransform), With<Obstacle>>,
+    spawn_query: Query<&Transform, With<SpawnPoint>>,
+    goal_query: Query<&Transform, With<Goal>>,
+) {
+    let Ok((placeholder_transform, mesh3d)) = placeholder_query.get_single() else {
+        return;
+    };
+    let tile = placeholder_transform.translation.xz();
+    if check.tile == Some(tile) {
+        return;
+    }
+    let Some(aabb) = meshes.get(&mesh3d.0).and_then(|mesh| mesh.compute_aabb()) else {
+        return;
+    };
+
+    // towers always sit on snapped tiles, so anything closer than half a tile shares it
+    let occupied = obstacle_query
+        .iter()
+        .any(|(_, transform)| transform.translation().xz().distance(tile) < SNAP_OFFSET);
+
+    let blocks_path = !occupied && {
+        let navmesh_transform = navmesh_query.single();
+        let to_navmesh = navmesh_transform.compute_matrix().inverse();
+        let mut obstacles: Vec<Vec<Vec2>> = obstacle_query
+            .iter()
+            .map(|(aabb, transform)| footprint(aabb, transform, &to_navmesh))
+            .collect();
+        obstacles.push(footprint(
+            &aabb,
+            &GlobalTransform::from(*placeholder_transform),
+            &to_navmesh,
+        ));
+
+        let mut navmesh = NavMesh::from_edge_and_obstacles(NAVMESH_BOUNDS.to_vec(), obstacles);
+        navmesh.set_transform(*navmesh_transform);
+        let flow_field =
+            FlowField::build(&navmesh, navmesh_transform, goal_query.single().translation);
+        spawn_query
+            .iter()
+            .any(|spawn| !flow_field.reaches_goal(spawn.translation))
+    };
+
+    check.tile = Some(tile);
+    check.valid = !occupied && !blocks_path;
+}
+
+// projects an obstacle's bounds onto the navmesh plane, the same area the navmesh updater cuts out
+fn footprint(aabb: &Aabb, transform: &GlobalTransform, to_navmesh: &Mat4) -> Vec<Vec2> {
+    let min = Vec3::from(aabb.min());
+    let max = Vec3::from(aabb.max());
+    [
+        Vec3::new(min.x, 0.0, min.z),
+        Vec3::new(max.x, 0.0, min.z),
+        Vec3::new(max.x, 0.0, max.z),
+        Vec3::new(min.x, 0.0, max.z),
+    ]
+    .into_iter()
+    .map(|corner| {
+        to_navmesh
+            .transform_point3(transform.transform_point(corner))
+            .xy()
+    })
+    .collect()
+}
+
+// shows the placeholder in red while the tile under it is not a valid placement
+fn tint_placeholder(
+    check: Res<PlacementCheck>,
+    placement_materials: Res<PlacementMaterials>,
+    mut query: Query<&mut MeshMaterial3d<StandardMaterial>, With<TowerPlaceholder>>,
+) {
+    if check.tile.is_none() || check.valid {
+        return;
+    }
+    for mut mat in query.iter_mut() {
+        mat.0 = placement_materials.invalid.clone();
+    }
+}
+
+// shows the stats of the tower under the cursor and lets the player pick what it shoots at
+fn inspect_tower(
+    mut contexts: EguiContexts,
+    cursor_query: Query<&Transform, With<CursorPlaceholder>>,
+    mut tower_query: Query<(&Transform, &mut Tower)>,
+    chart: Res<ElementChart>,
+) {
+    let Ok(cursor_transform) = cursor_query.get_single() else {
+        return;
+    };
+    let tile = snap_to_tile(cursor_transform.translation);
+    let Some((_, mut tower)) = tower_query
+        .iter_mut()
+        .find(|(transform, _)| transform.translation.xz().distance(tile) < SNAP_OFFSET)
+    else {
+        return;
+    };
+
+    let ctx = contexts.ctx_mut();
+
+    egui::Window::new(tower.name.clone())
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let matchups = BaseElementType::ALL
+                .iter()
+                .map(|defender| {
+                    format!(
+                        "vs {}: x{}",
+                        defender,
+                        chart.best_multiplier(
+                            tower.element_type,
+                            tower.secondary_element,
+                            *defender
+                        )
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let elements = match tower.secondary_element {
+                Some(secondary) => format!("{}/{}", tower.element_type, secondary),
+                None => tower.element_type.to_string(),
+            };
+            ui.label(format!("{} {}", tower.rarity, elements))
+                .on_hover_text(matchups);
+            ui.label(format!("Damage: {}", tower.damage));
+            ui.label(format!("Range: {:.1}", tower.range));
+            ui.label(format!(
+                "Fire rate: {:.2}/s",
+                1.0 / tower.attack_speed.duration().as_secs_f32()
+            ));
+
+            ui.separator();
+
+            let current = tower.targeting;
+            egui::ComboBox::from_label("Targeting")
+                .selected_text(current.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in TargetingMode::ALL {
+                        ui.selectable_value(&mut tower.targeting, mode, mode.to_string());
+                    }
+                });
+        });
+}
 
 fn start_wave(
     action_state: Res<ActionState<PlacementAction>>,
     mut next_state: ResMut<NextState<GamePlayState>>,
-    mut commands: Commands,
 ) {
     if action_state.just_pressed(&PlacementAction::EndPlacement) {
         next_state.set(GamePlayState::Wave);
-        commands.spawn(Wave {
-            timer: Timer::from_seconds(20.0, TimerMode::Once),
-        });
     }
 }
@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    AllAssets, BaseElementType, DieFace, DieRollResultEvent, GameResources, OwnedTower,
+    TowerDetails,
+};
+
+pub struct ResolutionPlugin;
+
+impl Plugin for ResolutionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, grant_tower.run_if(in_state(GameState::Game)));
+    }
+}
+
+/// Picks the tower a rolled face resolves into.
+///
+/// A tower is a candidate when it has the same elements as the face, in either order, and the
+/// face is at least as rare as the tower's `min_rarity`. The rarest candidate wins, so `game.ron`
+/// can gate stronger towers behind rarer faces. A dual element face without a hybrid tower of its
+/// own falls back to the towers of its primary element.
+pub fn resolve_tower<'a>(
+    face: &DieFace,
+    towers: impl Iterator<Item = (AssetId<TowerDetails>, &'a TowerDetails)>,
+) -> Option<AssetId<TowerDetails>> {
+    let towers = towers
+        .filter(|(_, tower)| tower.min_rarity <= face.rarity)
+        .collect::<Vec<_>>();
+    let rarest = |elements: [Option<BaseElementType>; 2]| {
+        towers
+            .iter()
+            .filter(|(_, tower)| {
+                let tower_elements = [Some(tower.element_type), tower.secondary_element];
+                tower_elements == elements || tower_elements == [elements[1], elements[0]]
+            })
+            .max_by_key(|(_, tower)| tower.min_rarity)
+            .map(|(id, _)| *id)
+    };
+    rarest([Some(face.primary_type), face.secondary_type])
+        .or_else(|| rarest([Some(face.primary_type), None]))
+}
+
+fn grant_tower(
+    mut game_resources: ResMut<GameResources>,
+    mut ev_result: EventReader<DieRollResultEvent>,
+    all_assets: Res<AllAssets>,
+    assets_towers: Res<Assets<TowerDetails>>,
+) {
+    for ev in ev_result.read() {
+        let face = ev.1;
+        let towers = all_assets.towers.iter().filter_map(|handle| {
+            assets_towers
+                .get(handle)
+                .map(|tower_details| (handle.id(), tower_details))
+        });
+        match resolve_tower(&face, towers) {
+            Some(details) => game_resources.towers.push(OwnedTower {
+                details,
+                rarity: face.rarity,
+                source_die: Some(ev.0),
+            }),
+            None => warn!("No tower resolves from a {} {} face", face.rarity, face),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::game::{Rarity, TowerStats};
+
+    fn tower(
+        element_type: BaseElementType,
+        secondary_element: Option<BaseElementType>,
+        min_rarity: Rarity,
+    ) -> TowerDetails {
+        TowerDetails {
+            name: format!("{} {:?}", element_type, secondary_element),
+            element_type,
+            secondary_element,
+            min_rarity,
+            stats: TowerStats {
+                range: 4.0,
+                damage: 5,
+                fire_rate: 1.0,
+                projectile_speed: 10.0,
+            },
+            projectile_mesh: Handle::default(),
+            projectile_material: Handle::default(),
+            model: Handle::default(),
+        }
+    }
+
+    fn face(
+        primary_type: BaseElementType,
+        secondary_type: Option<BaseElementType>,
+        rarity: Rarity,
+    ) -> DieFace {
+        DieFace {
+            primary_type,
+            secondary_type,
+            rarity,
+        }
+    }
+
+    fn id(n: u128) -> AssetId<TowerDetails> {
+        AssetId::Uuid {
+            uuid: Uuid::from_u128(n),
+        }
+    }
+
+    // the towers of `game.ron` in miniature: two fire towers a rarity apart, a fire and water
+    // hybrid and a water tower
+    fn resolve(face: DieFace) -> Option<AssetId<TowerDetails>> {
+        let towers = [
+            (id(1), tower(BaseElementType::Fire, None, Rarity::Common)),
+            (id(2), tower(BaseElementType::Fire, None, Rarity::Rare)),
+            (
+                id(3),
+                tower(
+                    BaseElementType::Fire,
+                    Some(BaseElementType::Water),
+                    Rarity::Common,
+                ),
+            ),
+            (id(4), tower(BaseElementType::Water, None, Rarity::Uncommon)),
+        ];
+        resolve_tower(&face, towers.iter().map(|(id, tower)| (*id, tower)))
+    }
+
+    #[test]
+    fn rarest_tower_the_face_is_rare_enough_for_wins() {
+        assert_eq!(
+            resolve(face(BaseElementType::Fire, None, Rarity::Common)),
+            Some(id(1))
+        );
+        assert_eq!(
+            resolve(face(BaseElementType::Fire, None, Rarity::Rare)),
+            Some(id(2))
+        );
+        assert_eq!(
+            resolve(face(BaseElementType::Fire, None, Rarity::Unique)),
+            Some(id(2))
+        );
+    }
+
+    #[test]
+    fn hybrid_towers_match_either_element_order() {
+        let water_fire = face(
+            BaseElementType::Water,
+            Some(BaseElementType::Fire),
+            Rarity::Common,
+        );
+        let fire_water = face(
+            BaseElementType::Fire,
+            Some(BaseElementType::Water),
+            Rarity::Common,
+        );
+        assert_eq!(resolve(water_fire), Some(id(3)));
+        assert_eq!(resolve(fire_water), Some(id(3)));
+    }
+
+    #[test]
+    fn dual_faces_without_a_hybrid_fall_back_to_their_primary_element() {
+        let fire_earth = face(
+            BaseElementType::Fire,
+            Some(BaseElementType::Earth),
+            Rarity::Rare,
+        );
+        assert_eq!(resolve(fire_earth), Some(id(2)));
+    }
+
+    #[test]
+    fn nothing_resolves_without_a_matching_tower() {
+        // no earth tower at all
+        assert_eq!(
+            resolve(face(BaseElementType::Earth, None, Rarity::Unique)),
+            None
+        );
+        // the only water tower needs an uncommon face
+        assert_eq!(
+            resolve(face(BaseElementType::Water, None, Rarity::Common)),
+            None
+        );
+    }
+}
@@ -3,30 +3,51 @@ use bevy_egui::{egui, EguiContexts};
 
 use crate::GameState;
 
-use super::dice_physics::{DicePhysicsPlugin, ThrowPower};
-use super::{DieRolledEvent, GamePlayState, GameResources, Rarity};
+use super::dice_physics::DicePhysicsPlugin;
+use super::throw::{ThrowGesture, ThrowPlugin};
+use super::{GamePlayState, GameResources, Rarity};
+
+// Results that can be thrown away and rolled again every round, on top of any bought
+const REROLLS_PER_ROUND: u32 = 2;
 
 pub struct RollPlugin;
 
 impl Plugin for RollPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(DicePhysicsPlugin).add_systems(
-            Update,
-            rolling_ui.run_if(in_state(GameState::Game).and(in_state(GamePlayState::Rolling))),
-        );
+        app.add_plugins((DicePhysicsPlugin, ThrowPlugin))
+            .add_systems(OnEnter(GamePlayState::Rolling), start_round)
+            .add_systems(
+                Update,
+                rolling_ui.run_if(in_state(GameState::Game).and(in_state(GamePlayState::Rolling))),
+            );
     }
 }
 
+// every round starts with every die unrolled and a fresh reroll budget
+fn start_round(mut game_resources: ResMut<GameResources>) {
+    for die in game_resources.dice.iter_mut() {
+        die.result = None;
+        die.rolling = false;
+    }
+    // towers left over from earlier rounds are kept whatever the dice roll now
+    for tower in game_resources.towers.iter_mut() {
+        tower.source_die = None;
+    }
+    game_resources.rerolls = REROLLS_PER_ROUND + game_resources.bought_rerolls;
+    game_resources.bought_rerolls = 0;
+}
+
 fn rolling_ui(
     mut contexts: EguiContexts,
     mut game_resources: ResMut<GameResources>,
-    mut ev_rolled: EventWriter<DieRolledEvent>,
-    mut throw_power: ResMut<ThrowPower>,
+    mut gesture: ResMut<ThrowGesture>,
     mut next_state: ResMut<NextState<GamePlayState>>,
 ) {
     let ctx = contexts.ctx_mut();
+    let mut reroll = None;
 
-    egui::CentralPanel::default()
+    // a side panel leaves the rest of the screen free to drag throws on
+    egui::SidePanel::left("rolling_panel")
         .frame(egui::Frame::none())
         .show(ctx, |ui| {
             ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
@@ -51,8 +72,9 @@ fn rolling_ui(
                         .show(ui, |ui| {
                             ui.label(
                                 egui::RichText::new(format!(
-                                    "Die #{}",
-                                    game_resources.highlighted_die + 1
+                                    "{} ({})",
+                                    game_resources.dice[game_resources.highlighted_die].name,
+                                    game_resources.dice[game_resources.highlighted_die].shape
                                 ))
                                 .size(18.0),
                             );
@@ -90,23 +112,11 @@ fn rolling_ui(
                                 };
 
                                 ui.label(
-                                    egui::RichText::new(format!(
-                                        "{}. {}",
-                                        i + 1,
-                                        face.primary_type
-                                    ))
-                                    .color(color),
+                                    egui::RichText::new(format!("{}. {}", i + 1, face))
+                                        .color(color),
                                 );
                             }
 
-                            ui.horizontal(|ui| {
-                                ui.label("Throw Power:");
-                                ui.add(
-                                    egui::Slider::new(&mut throw_power.0, 0.1..=1.0)
-                                        .show_value(true),
-                                );
-                            });
-
                             if let Some(result) = &current_die.result {
                                 let color = match result.rarity {
                                     Rarity::Common => egui::Color32::WHITE,
@@ -119,37 +129,113 @@ fn rolling_ui(
                                 ui.horizontal(|ui| {
                                     ui.label(egui::RichText::new("Result:").strong());
                                     ui.label(
-                                        egui::RichText::new(format!("{}", result.primary_type))
+                                        egui::RichText::new(result.to_string())
                                             .size(24.0)
                                             .color(color),
                                     );
                                 });
+
+                                // throws the result away, along with the tower it was worth
+                                if ui
+                                    .add_enabled(
+                                        game_resources.rerolls > 0 && !current_die.rolling,
+                                        egui::Button::new(format!(
+                                            "Reroll ({} left)",
+                                            game_resources.rerolls
+                                        )),
+                                    )
+                                    .clicked()
+                                {
+                                    reroll = Some(current_die.id);
+                                }
                             }
 
                             // todo: fix can roll logic not working
-                            if ui
-                                .add_enabled(
-                                    current_die.result.is_none() && !current_die.rolling,
-                                    egui::Button::new("Roll"),
-                                )
-                                .clicked()
-                            {
-                                ev_rolled.send(DieRolledEvent(current_die.clone()));
-                            }
+                            let can_roll = current_die.result.is_none() && !current_die.rolling;
+                            let mut selected = gesture.armed.contains(&current_die.id);
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .add_enabled(can_roll, egui::Button::new("Roll"))
+                                    .clicked()
+                                {
+                                    gesture.armed = vec![current_die.id];
+                                }
+                                if ui
+                                    .add_enabled(
+                                        can_roll,
+                                        egui::Checkbox::new(&mut selected, "Add to throw"),
+                                    )
+                                    .changed()
+                                {
+                                    if selected {
+                                        gesture.armed.push(current_die.id);
+                                    } else {
+                                        gesture.armed.retain(|id| *id != current_die.id);
+                                    }
+                                }
+                            });
                         });
                 });
 
-                // Start Placement button
+                // every die that still has to be rolled, thrown in one go
+                if ui
+                    .add(egui::Button::new(
+                        egui::RichText::new("Roll All").size(24.0),
+                    ))
+                    .clicked()
+                {
+                    gesture.armed = game_resources
+                        .dice
+                        .iter()
+                        .filter(|die| die.result.is_none() && !die.rolling)
+                        .map(|die| die.id)
+                        .collect();
+                }
+
+                if !gesture.armed.is_empty() {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Drag with the mouse or flick the right stick to throw {} {}",
+                            gesture.armed.len(),
+                            if gesture.armed.len() == 1 {
+                                "die"
+                            } else {
+                                "dice"
+                            }
+                        ))
+                        .color(egui::Color32::YELLOW),
+                    );
+                }
+
+                // Combos are tallied before the towers are placed
                 if ui
                     .add_enabled(
                         game_resources.towers.len() > 0,
-                        egui::Button::new(egui::RichText::new("Continue to Placement").size(24.0))
+                        egui::Button::new(egui::RichText::new("Continue").size(24.0))
                             .min_size(egui::vec2(250.0, 40.0)),
                     )
                     .clicked()
                 {
-                    next_state.set(GamePlayState::Placement);
+                    next_state.set(GamePlayState::Combo);
                 }
             });
         });
+
+    if let Some(id) = reroll {
+        game_resources.rerolls -= 1;
+        if let Some(die) = game_resources.dice.iter_mut().find(|die| die.id == id) {
+            die.result = None;
+        }
+        if let Some(i) = game_resources
+            .towers
+            .iter()
+            .rposition(|tower| tower.source_die == Some(id))
+        {
+            game_resources.towers.remove(i);
+            if game_resources.highlighted_tower >= game_resources.towers.len() {
+                game_resources.highlighted_tower = 0;
+            }
+        }
+        gesture.armed = vec![id];
+    }
 }
@@ -0,0 +1,216 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+use leafwing_input_manager::{prelude::*, Actionlike, InputControlKind};
+use std::f32::consts::PI;
+use uuid::Uuid;
+
+use crate::GameState;
+
+use super::{camera::FollowCam, DieRolledEvent, GamePlayState};
+
+// Pixels the mouse has to be dragged for a full power throw
+const FULL_POWER_DRAG: f32 = 300.0;
+// Stick deflection below which the stick counts as released
+const FLICK_DEADZONE: f32 = 0.2;
+// Gestures shorter than this are taken as a misclick and ignored
+const MIN_GESTURE: f32 = 0.05;
+// Side spin of a fully curled throw, in radians per second
+const MAX_CURL_SPIN: f32 = PI * 4.0;
+
+pub struct ThrowPlugin;
+
+impl Plugin for ThrowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(InputManagerPlugin::<ThrowAction>::default())
+            .init_resource::<ActionState<ThrowAction>>()
+            .insert_resource(ThrowAction::default_input_map())
+            .init_resource::<ThrowGesture>()
+            .add_systems(
+                Update,
+                (track_throw_gesture, draw_throw_preview)
+                    .run_if(in_state(GameState::Game).and(in_state(GamePlayState::Rolling))),
+            )
+            .add_systems(OnExit(GamePlayState::Rolling), reset_throw_gesture);
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
+enum ThrowAction {
+    Drag,
+    Hold,
+    Flick,
+}
+
+impl Actionlike for ThrowAction {
+    fn input_control_kind(&self) -> InputControlKind {
+        match self {
+            ThrowAction::Drag => InputControlKind::DualAxis,
+            ThrowAction::Hold => InputControlKind::Button,
+            ThrowAction::Flick => InputControlKind::DualAxis,
+        }
+    }
+}
+
+impl ThrowAction {
+    /// Define the default bindings to the input
+    fn default_input_map() -> InputMap<Self> {
+        let mut input_map = InputMap::default();
+
+        // Default gamepad input bindings
+        input_map.insert_dual_axis(Self::Flick, GamepadStick::RIGHT);
+
+        // // Default kbm input bindings
+        input_map.insert_dual_axis(Self::Drag, MouseMove::default());
+        input_map.insert(Self::Hold, MouseButton::Left);
+
+        input_map
+    }
+}
+
+/// How a die leaves the hand, picked with a throw gesture.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub struct Throw {
+    // direction on screen, up is away from the camera
+    pub aim: Vec2,
+    // 0..=1, how hard the die is thrown
+    pub power: f32,
+    // -1..=1, side spin from a gesture curling to the left or to the right
+    pub curl: f32,
+}
+
+impl Throw {
+    /// Where a die thrown from the camera leaves the hand.
+    pub fn origin(camera: &Transform) -> Vec3 {
+        camera.translation + camera.forward() * 2.0
+    }
+
+    /// Linear and angular velocity of a die thrown from the camera.
+    pub fn launch(&self, camera: &Transform) -> (Vec3, Vec3) {
+        let forward = camera.forward().with_y(0.0).normalize_or_zero();
+        let right = camera.right().with_y(0.0).normalize_or_zero();
+        let heading = (right * self.aim.x + forward * self.aim.y).normalize_or(forward);
+
+        // a little upwards so it arcs onto the table
+        let linear_velocity = (heading + Vec3::Y * 0.5).normalize() * (self.power * 10.0 + 5.0);
+        // tumbles forward the harder it is thrown, and spins sideways with the curl
+        let angular_velocity = Vec3::Y.cross(heading) * (self.power * PI * 4.0 + PI)
+            + Vec3::Y * self.curl * MAX_CURL_SPIN;
+        (linear_velocity, angular_velocity)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GestureInput {
+    Mouse,
+    Stick,
+}
+
+/// The throw being lined up with the mouse or the right stick.
+#[derive(Resource, Debug, Default)]
+pub struct ThrowGesture {
+    /// The dice the next gesture throws together, picked from the rolling menu.
+    pub armed: Vec<Uuid>,
+    input: Option<GestureInput>,
+    // how far the gesture has pulled, a length of one is a full power throw
+    aim: Vec2,
+    // area swept by the gesture, positive when it curls to the left
+    curl: f32,
+}
+
+impl ThrowGesture {
+    fn start(&mut self, input: GestureInput) {
+        self.input = Some(input);
+        self.aim = Vec2::ZERO;
+        self.curl = 0.0;
+    }
+
+    fn pull(&mut self, aim: Vec2) {
+        self.curl += self.aim.perp_dot(aim - self.aim);
+        self.aim = aim;
+    }
+
+    fn throw(&self) -> Throw {
+        Throw {
+            aim: self.aim.normalize_or_zero(),
+            power: self.aim.length().clamp(0.1, 1.0),
+            curl: self.curl.clamp(-1.0, 1.0),
+        }
+    }
+}
+
+// drag with the mouse button held, or pull the stick and let it snap back
+fn track_throw_gesture(
+    mut contexts: EguiContexts,
+    action_state: Res<ActionState<ThrowAction>>,
+    mut gesture: ResMut<ThrowGesture>,
+    mut ev_rolled: EventWriter<DieRolledEvent>,
+) {
+    if gesture.armed.is_empty() {
+        return;
+    }
+
+    let stick = action_state.clamped_axis_pair(&ThrowAction::Flick);
+    let released = match gesture.input {
+        None => {
+            if action_state.just_pressed(&ThrowAction::Hold)
+                && !contexts.ctx_mut().is_pointer_over_area()
+            {
+                gesture.start(GestureInput::Mouse);
+            } else if stick.length() > FLICK_DEADZONE {
+                gesture.start(GestureInput::Stick);
+            }
+            false
+        }
+        Some(GestureInput::Mouse) => {
+            // mouse motion counts y down the screen, aim counts it up like the stick does
+            let drag = action_state.axis_pair(&ThrowAction::Drag) * Vec2::new(1.0, -1.0);
+            let aim = gesture.aim + drag / FULL_POWER_DRAG;
+            gesture.pull(aim);
+            action_state.just_released(&ThrowAction::Hold)
+        }
+        Some(GestureInput::Stick) => {
+            // the furthest the stick was pulled sets the throw
+            if stick.length() >= gesture.aim.length() {
+                gesture.pull(stick);
+            }
+            stick.length() <= FLICK_DEADZONE
+        }
+    };
+    if !released {
+        return;
+    }
+
+    if gesture.aim.length() >= MIN_GESTURE {
+        let throw = gesture.throw();
+        for die in gesture.armed.drain(..) {
+            ev_rolled.send(DieRolledEvent(die, throw));
+        }
+    }
+    gesture.input = None;
+}
+
+// the arc the die would fly along if the gesture was let go now, until it drops below the table
+fn draw_throw_preview(
+    mut gizmos: Gizmos,
+    gesture: Res<ThrowGesture>,
+    gravity: Res<Gravity>,
+    camera_query: Query<&Transform, With<FollowCam>>,
+) {
+    if gesture.input.is_none() {
+        return;
+    }
+    let camera = camera_query.single();
+    let origin = Throw::origin(camera);
+    let (velocity, _) = gesture.throw().launch(camera);
+
+    let points = (0..40)
+        .map(|step| step as f32 * 0.05)
+        .map(|t| origin + velocity * t + 0.5 * gravity.0 * t * t)
+        .take_while(|point| point.y > -1.0);
+    gizmos.linestrip(points, Color::srgb(1.0, 0.9, 0.3));
+}
+
+fn reset_throw_gesture(mut gesture: ResMut<ThrowGesture>) {
+    *gesture = ThrowGesture::default();
+}
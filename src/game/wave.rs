@@ -1,43 +1,68 @@
+use std::f32::consts::FRAC_PI_2;
 use std::time::Duration;
 
 use bevy::{gltf::GltfMesh, prelude::*};
+use rand::Rng;
 use vleue_navigator::prelude::*;
 
-use crate::GameState;
+use crate::{despawn_screen, GameState};
 
 use super::{
-    placement::{Projectile, Tower},
-    EnemyDetails, GamePlayState, GameResources, Goal, Wave,
+    camera::FollowCam,
+    flow_field::FlowField,
+    placement::{Projectile, TargetingMode, Tower},
+    AllAssets, BaseElementType, ElementChart, EnemyDetails, GamePlayState, GameResources, Goal,
+    RunRng, SpawnPoint, WaveDetails,
 };
 
 pub struct WavePlugin;
 
 impl Plugin for WavePlugin {
     fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GamePlayState::Wave), start_wave)
+            .add_systems(
+                Update,
+                (
+                    spawn_enemy,
+                    move_enemy,
+                    tower_shooting,
+                    move_projectile,
+                    bullet_despawn,
+                    bullet_collision,
+                    target_death,
+                    enemy_goal_collision,
+                    update_damage_numbers,
+                    tick_status_effects,
+                    draw_status_effects,
+                    // runs before spawning so an enemy spawned this frame is seen next frame
+                    end_wave.before(spawn_enemy),
+                )
+                    .run_if(in_state(GameState::Game).and(in_state(GamePlayState::Wave))),
+            )
+            .add_systems(
+                OnExit(GamePlayState::Wave),
+                (
+                    despawn_screen::<EnemySpawner>,
+                    despawn_screen::<DamageNumber>,
+                ),
+            );
+        #[cfg(feature = "debug")]
         app.add_systems(
             Update,
-            (
-                spawn_enemy,
-                find_path,
-                move_enemy,
-                tower_shooting,
-                move_projectile,
-                bullet_despawn,
-                bullet_collision,
-                target_death,
-                enemy_goal_collision,
-                end_wave,
-            )
+            (spawn_swarm, check_frame_budget)
                 .run_if(in_state(GameState::Game).and(in_state(GamePlayState::Wave))),
         );
     }
 }
 
+/// Spawns one group of a wave: `remaining` enemies, one every `interval` once `delay` is over.
 #[derive(Reflect, Component, Default)]
 #[reflect(Component)]
 pub struct EnemySpawner {
-    pub delta: Timer,
-    pub total_time: Timer,
+    pub enemy: String,
+    pub remaining: u32,
+    pub delay: Timer,
+    pub interval: Timer,
 }
 
 #[derive(Reflect, Component, Default)]
@@ -46,6 +71,177 @@ pub struct Enemy {
     name: String,
     health: u32,
     speed: f32,
+    damage: u32,
+    element_type: BaseElementType,
+}
+
+/// Lingering effects of elemental hits on an enemy.
+///
+/// Burns stack up to [`MAX_BURN_STACKS`], each ticking on its own. Slows and stuns do not stack:
+/// a new one refreshes the duration and keeps the stronger slow. Wind knockback is instant and
+/// never stored here.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+#[derive(Reflect, Debug, Clone)]
+pub struct StatusEffect {
+    kind: StatusKind,
+    duration: Timer,
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+enum StatusKind {
+    // damage dealt every `BURN_TICK` seconds
+    Burn(u32),
+    // fraction of speed taken away
+    Slow(f32),
+    Stun,
+}
+
+const MAX_BURN_STACKS: usize = 3;
+const BURN_TICK: f32 = 1.0;
+const KNOCKBACK_DISTANCE: f32 = 0.5;
+// enemies of a kind walk up to this fraction faster or slower than each other so groups spread out
+const SPEED_VARIATION: f32 = 0.1;
+
+impl StatusEffect {
+    fn new(kind: StatusKind, seconds: f32) -> Self {
+        StatusEffect {
+            kind,
+            duration: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+
+    // what a hit of the given element leaves behind, wind knockback is handled on hit
+    fn from_element(element: BaseElementType) -> Option<Self> {
+        match element {
+            BaseElementType::Fire => Some(StatusEffect::new(StatusKind::Burn(1), 3.0)),
+            BaseElementType::Water => Some(StatusEffect::new(StatusKind::Slow(0.4), 2.0)),
+            BaseElementType::Earth => Some(StatusEffect::new(StatusKind::Stun, 0.5)),
+            BaseElementType::Wind | BaseElementType::None => None,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self.kind {
+            StatusKind::Burn(_) => Color::srgb(1.0, 0.4, 0.0),
+            StatusKind::Slow(_) => Color::srgb(0.2, 0.5, 1.0),
+            StatusKind::Stun => Color::srgb(1.0, 1.0, 0.2),
+        }
+    }
+}
+
+impl StatusEffects {
+    fn apply(&mut self, effect: StatusEffect) {
+        match effect.kind {
+            StatusKind::Burn(_) => {
+                let burns = self
+                    .0
+                    .iter()
+                    .filter(|existing| matches!(existing.kind, StatusKind::Burn(_)))
+                    .count();
+                if burns < MAX_BURN_STACKS {
+                    self.0.push(effect);
+                } else if let Some(oldest) = self
+                    .0
+                    .iter_mut()
+                    .filter(|existing| matches!(existing.kind, StatusKind::Burn(_)))
+                    .min_by_key(|existing| existing.duration.remaining())
+                {
+                    oldest.duration.reset();
+                }
+            }
+            StatusKind::Slow(amount) => {
+                match self
+                    .0
+                    .iter_mut()
+                    .find(|existing| matches!(existing.kind, StatusKind::Slow(_)))
+                {
+                    Some(existing) => {
+                        if let StatusKind::Slow(current) = existing.kind {
+                            existing.kind = StatusKind::Slow(current.max(amount));
+                        }
+                        existing.duration = effect.duration;
+                    }
+                    None => self.0.push(effect),
+                }
+            }
+            StatusKind::Stun => {
+                match self
+                    .0
+                    .iter_mut()
+                    .find(|existing| existing.kind == StatusKind::Stun)
+                {
+                    Some(existing) => {
+                        if effect.duration.remaining() > existing.duration.remaining() {
+                            existing.duration = effect.duration;
+                        }
+                    }
+                    None => self.0.push(effect),
+                }
+            }
+        }
+    }
+
+    /// Fraction of its normal speed the enemy currently moves at.
+    fn speed_multiplier(&self) -> f32 {
+        self.0
+            .iter()
+            .fold(1.0, |multiplier, effect| match effect.kind {
+                StatusKind::Stun => 0.0,
+                StatusKind::Slow(amount) => multiplier * (1.0 - amount),
+                StatusKind::Burn(_) => multiplier,
+            })
+    }
+}
+
+// Floating readout of a hit, drawn in screen space over the enemy it hit
+#[derive(Component)]
+struct DamageNumber {
+    position: Vec3,
+    lifetime: Timer,
+}
+
+// the wave director: turns the next wave in game.ron into one spawner per group
+fn start_wave(
+    mut commands: Commands,
+    game_resources: Res<GameResources>,
+    all_assets: Res<AllAssets>,
+    assets_waves: Res<Assets<WaveDetails>>,
+    spawn_points: Query<(&SpawnPoint, &Transform)>,
+) {
+    // once every wave has been cleared, the last one keeps repeating
+    let Some(handle) = all_assets
+        .waves
+        .get(game_resources.wave)
+        .or(all_assets.waves.last())
+    else {
+        warn!("No waves defined");
+        return;
+    };
+    let wave = assets_waves.get(handle).unwrap();
+    info!("Wave {} started", game_resources.wave + 1);
+
+    for group in wave.groups.iter() {
+        let Some((_, spawn_transform)) = spawn_points
+            .iter()
+            .find(|(spawn_point, _)| spawn_point.0 == group.spawn_point)
+        else {
+            warn!("No spawn point {} for {}", group.spawn_point, group.enemy);
+            continue;
+        };
+        commands.spawn((
+            Transform::from_translation(spawn_transform.translation),
+            EnemySpawner {
+                enemy: group.enemy.clone(),
+                remaining: group.count,
+                delay: Timer::from_seconds(group.delay, TimerMode::Once),
+                interval: Timer::from_seconds(group.interval, TimerMode::Repeating),
+            },
+            Name::new(format!("{} Spawner", group.enemy)),
+        ));
+    }
 }
 
 fn spawn_enemy(
@@ -54,104 +250,165 @@ fn spawn_enemy(
     assets_gltfmesh: Res<Assets<GltfMesh>>,
     res: Res<Assets<Gltf>>,
     time: Res<Time>,
+    mut rng: ResMut<RunRng>,
     mut query: Query<(&mut EnemySpawner, &Transform)>,
 ) {
     for (mut spawner, transform) in query.iter_mut() {
-        spawner.total_time.tick(time.delta());
-        if spawner.total_time.finished() {
-            return;
+        if spawner.remaining == 0 {
+            continue;
+        }
+
+        spawner.delay.tick(time.delta());
+        if !spawner.delay.finished() {
+            continue;
         }
 
-        spawner.delta.tick(time.delta());
-        if spawner.delta.finished() {
-            let enemy = assets_enemies.iter().next().unwrap().1;
-            let enemy_mesh = res.get(&enemy.model).unwrap();
-            let enemy_mesh_mesh = assets_gltfmesh.get(&enemy_mesh.meshes[0]).unwrap();
-
-            commands.spawn((
-                Mesh3d(enemy_mesh_mesh.primitives[0].mesh.clone()),
-                MeshMaterial3d(enemy_mesh.materials[0].clone()),
-                transform.with_scale(Vec3::splat(0.5)),
-                Enemy {
-                    name: enemy.name.clone(),
-                    health: enemy.health,
-                    speed: enemy.speed,
-                },
-            ));
+        // the first enemy of a group leaves as soon as the delay is over
+        let due =
+            spawner.delay.just_finished() || spawner.interval.tick(time.delta()).just_finished();
+        if !due {
+            continue;
         }
+
+        let Some((_, enemy)) = assets_enemies
+            .iter()
+            .find(|(_, enemy)| enemy.name == spawner.enemy)
+        else {
+            warn!("Unknown enemy: {}", spawner.enemy);
+            spawner.remaining = 0;
+            continue;
+        };
+        let enemy_mesh = res.get(&enemy.model).unwrap();
+        let enemy_mesh_mesh = assets_gltfmesh.get(&enemy_mesh.meshes[0]).unwrap();
+
+        commands.spawn((
+            Mesh3d(enemy_mesh_mesh.primitives[0].mesh.clone()),
+            MeshMaterial3d(enemy_mesh.materials[0].clone()),
+            transform.with_scale(Vec3::splat(0.5)),
+            Enemy {
+                name: enemy.name.clone(),
+                health: enemy.health,
+                speed: enemy.speed * (1.0 + rng.0.gen_range(-SPEED_VARIATION..=SPEED_VARIATION)),
+                damage: enemy.damage,
+                element_type: enemy.element_type,
+            },
+            StatusEffects::default(),
+        ));
+        spawner.remaining -= 1;
     }
 }
 
-pub fn find_path(
-    mut navmeshes: ResMut<Assets<NavMesh>>,
-    navmesh: Query<(&ManagedNavMesh, &NavMeshStatus)>,
-    mut from_query: Query<&mut Transform, With<Enemy>>,
-    to_query: Query<&Transform, (With<Goal>, Without<Enemy>)>,
+fn move_enemy(
+    time: Res<Time>,
+    flow_field: Res<FlowField>,
+    navmeshes: Res<Assets<NavMesh>>,
+    navmesh_query: Query<&ManagedNavMesh>,
+    goal_query: Query<&Transform, (With<Goal>, Without<Enemy>)>,
+    mut query: Query<(&mut Transform, &Enemy, &StatusEffects)>,
 ) {
-    let (navmesh_handle, status) = navmesh.single();
-    if *status != NavMeshStatus::Built {
-        return;
-    }
-    if let Some(navmesh) = navmeshes.get_mut(navmesh_handle) {
-        let to = to_query.single().translation;
-        from_query.iter_mut().for_each(|mut from| {
-            if let Some(path) = navmesh.transformed_path(from.translation, to) {
-                let next = path.path[0];
-                from.look_at(Vec3::new(next.x, next.y, next.z), Vec3::Y);
-            } else {
-                warn_once!("no path found from {:?} to {:?}", from, to);
-            }
+    let navmesh = navmesh_query
+        .get_single()
+        .ok()
+        .and_then(|handle| navmeshes.get(handle));
+    let goal = goal_query.get_single().ok().map(|goal| goal.translation);
+    for (mut transform, enemy, effects) in query.iter_mut() {
+        // an enemy the field cannot steer, cut off from its cells or knocked off the grid, follows
+        // the navmesh instead, or heads straight for the goal so the wave can still end
+        let position = transform.translation;
+        let direction = flow_field.direction(position).or_else(|| {
+            let goal = goal?;
+            let next = navmesh
+                .and_then(|navmesh| navmesh.transformed_path(position, goal))
+                .and_then(|path| path.path.first().copied())
+                .unwrap_or(goal);
+            (next - position).with_y(0.0).try_normalize()
         });
+        let Some(direction) = direction else {
+            continue;
+        };
+        transform.look_to(direction, Vec3::Y);
+        transform.translation +=
+            direction * enemy.speed * effects.speed_multiplier() * time.delta_secs();
     }
 }
 
-fn move_enemy(mut query: Query<&mut Transform, With<Enemy>>) {
-    for mut transform in query.iter_mut() {
-        let forward = transform.forward();
-        transform.translation += forward * 0.01;
-        // base rotate off of z translation
-        transform.rotation = Quat::from_rotation_z((transform.translation.z * 8.0).sin() * 0.1);
-    }
-}
-
+// each tower fires at most once per frame, at the enemy in range its targeting mode prefers
 fn tower_shooting(
     mut commands: Commands,
-    query: Query<(Entity, &Transform), With<Enemy>>,
+    query: Query<(Entity, &Enemy, &Transform)>,
     mut query_tower: Query<(&Transform, &mut Tower)>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    flow_field: Res<FlowField>,
     time: Res<Time>,
 ) {
-    for (enemy, enemy_transform) in query.iter() {
-        for (tower_transform, mut tower) in query_tower.iter_mut() {
-            tower.attack_speed.tick(time.delta());
-            if tower.attack_speed.finished() {
-                let bullet_spawn = tower_transform.translation; //  + tower.bullet_offset;
+    for (tower_transform, mut tower) in query_tower.iter_mut() {
+        tower.attack_speed.tick(time.delta());
+        if !tower.attack_speed.finished() {
+            continue;
+        }
 
-                let distance = tower_transform
+        let target = query
+            .iter()
+            .filter(|(_, _, enemy_transform)| {
+                tower_transform
                     .translation
-                    .distance(enemy_transform.translation);
-
-                let placeholder_mesh = meshes.add(Sphere::new(0.1));
-                if distance < 5.0 {
-                    commands.spawn((
-                        Mesh3d(placeholder_mesh.clone()),
-                        MeshMaterial3d(materials.add(StandardMaterial {
-                            base_color: Color::srgb(1.0, 0.0, 0.0),
-                            ..Default::default()
-                        })),
-                        Transform::from_translation(bullet_spawn),
-                        Projectile {
-                            target: enemy,
-                            speed: 10.0,
-                            damage: 5,
-                            lifetime: Timer::new(Duration::from_secs(5), TimerMode::Once),
-                        },
-                    ));
-                    tower.attack_speed.reset();
-                }
-            }
-        }
+                    .distance(enemy_transform.translation)
+                    < tower.range
+            })
+            .min_by(|(_, a, a_transform), (_, b, b_transform)| {
+                let a = target_priority(
+                    tower.targeting,
+                    a,
+                    a_transform,
+                    &flow_field,
+                    tower_transform,
+                );
+                let b = target_priority(
+                    tower.targeting,
+                    b,
+                    b_transform,
+                    &flow_field,
+                    tower_transform,
+                );
+                a.total_cmp(&b)
+            });
+        let Some((enemy, _, _)) = target else {
+            continue;
+        };
+
+        let bullet_spawn = tower_transform.translation; //  + tower.bullet_offset;
+        commands.spawn((
+            Mesh3d(tower.projectile_mesh.clone()),
+            MeshMaterial3d(tower.projectile_material.clone()),
+            Transform::from_translation(bullet_spawn),
+            Projectile {
+                target: enemy,
+                speed: tower.projectile_speed,
+                damage: tower.damage,
+                element_type: tower.element_type,
+                secondary_element: tower.secondary_element,
+                lifetime: Timer::new(Duration::from_secs(5), TimerMode::Once),
+            },
+        ));
+        tower.attack_speed.reset();
+    }
+}
+
+// lower is preferred
+fn target_priority(
+    mode: TargetingMode,
+    enemy: &Enemy,
+    enemy_transform: &Transform,
+    flow_field: &FlowField,
+    tower_transform: &Transform,
+) -> f32 {
+    match mode {
+        TargetingMode::First => flow_field.distance_to_goal(enemy_transform.translation),
+        TargetingMode::Last => -flow_field.distance_to_goal(enemy_transform.translation),
+        TargetingMode::Strongest => -(enemy.health as f32),
+        TargetingMode::Weakest => enemy.health as f32,
+        TargetingMode::Closest => enemy_transform
+            .translation
+            .distance(tower_transform.translation),
     }
 }
 
@@ -192,36 +449,159 @@ fn bullet_despawn(
 fn bullet_collision(
     mut commands: Commands,
     bullets: Query<(Entity, &GlobalTransform, &Projectile), With<Projectile>>,
-    mut targets: Query<(&mut Enemy, &Transform), With<Enemy>>,
+    mut targets: Query<(&mut Enemy, &mut Transform, &mut StatusEffects), With<Enemy>>,
+    chart: Res<ElementChart>,
 ) {
     for (bullet, bullet_transform, projectile) in &bullets {
-        for (mut enemy, target_transform) in &mut targets {
+        for (mut enemy, mut target_transform, mut effects) in &mut targets {
             if Vec3::distance(bullet_transform.translation(), target_transform.translation) < 0.4 {
                 commands.entity(bullet).despawn_recursive();
-                enemy.health = enemy
-                    .health
-                    .checked_sub(projectile.damage)
-                    .unwrap_or_default();
+                let multiplier = chart.best_multiplier(
+                    projectile.element_type,
+                    projectile.secondary_element,
+                    enemy.element_type,
+                );
+                let damage = (projectile.damage as f32 * multiplier).round() as u32;
+                enemy.health = enemy.health.saturating_sub(damage);
+                spawn_damage_number(
+                    &mut commands,
+                    target_transform.translation,
+                    damage,
+                    multiplier,
+                );
+
+                // a hybrid projectile leaves the effects of both its elements
+                for element in
+                    std::iter::once(projectile.element_type).chain(projectile.secondary_element)
+                {
+                    if element == BaseElementType::Wind {
+                        // back the way it came, the flow field steers it on again
+                        let back = target_transform.back();
+                        target_transform.translation += back * KNOCKBACK_DISTANCE;
+                    } else if let Some(effect) = StatusEffect::from_element(element) {
+                        effects.apply(effect);
+                    }
+                }
                 break;
             }
         }
     }
 }
 
+fn spawn_damage_number(commands: &mut Commands, position: Vec3, damage: u32, multiplier: f32) {
+    let (text, color) = if multiplier > 1.0 {
+        (
+            format!("{} x{}", damage, multiplier),
+            Color::srgb(1.0, 0.8, 0.0),
+        )
+    } else if multiplier < 1.0 {
+        (
+            format!("{} x{}", damage, multiplier),
+            Color::srgb(0.6, 0.6, 0.6),
+        )
+    } else {
+        (damage.to_string(), Color::WHITE)
+    };
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        Text::new(text),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(color),
+        // hidden until it has been placed over the enemy
+        Visibility::Hidden,
+        DamageNumber {
+            position: position + Vec3::Y * 0.5,
+            lifetime: Timer::from_seconds(0.8, TimerMode::Once),
+        },
+    ));
+}
+
+fn update_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<FollowCam>>,
+    mut query: Query<(Entity, &mut DamageNumber, &mut Node, &mut Visibility)>,
+) {
+    let (camera, camera_transform) = camera_query.single();
+    for (entity, mut number, mut node, mut visibility) in query.iter_mut() {
+        number.lifetime.tick(time.delta());
+        if number.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // drift upwards while it fades out
+        number.position.y += time.delta_secs();
+        match camera.world_to_viewport(camera_transform, number.position) {
+            Ok(viewport_position) => {
+                node.left = Val::Px(viewport_position.x);
+                node.top = Val::Px(viewport_position.y);
+                *visibility = Visibility::Inherited;
+            }
+            Err(_) => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
 fn enemy_goal_collision(
     mut commands: Commands,
     goals: Query<&Transform, With<Goal>>,
-    enemies: Query<(Entity, &Transform), With<Enemy>>,
+    enemies: Query<(Entity, &Enemy, &Transform)>,
+    mut game_resources: ResMut<GameResources>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
     for goal_transform in &goals {
-        for (entity, enemy_transform) in &enemies {
+        for (entity, enemy, enemy_transform) in &enemies {
             if Vec3::distance(goal_transform.translation, enemy_transform.translation) < 0.4 {
                 commands.entity(entity).despawn_recursive();
+                game_resources.lives = game_resources.lives.saturating_sub(enemy.damage);
+                info!(
+                    "{} reached the goal, {} lives left",
+                    enemy.name, game_resources.lives
+                );
+                if game_resources.lives == 0 {
+                    next_state.set(GameState::GameOver);
+                }
             }
         }
     }
 }
 
+fn tick_status_effects(time: Res<Time>, mut query: Query<(&mut Enemy, &mut StatusEffects)>) {
+    for (mut enemy, mut effects) in query.iter_mut() {
+        for effect in effects.0.iter_mut() {
+            let before = effect.duration.elapsed_secs();
+            effect.duration.tick(time.delta());
+            if let StatusKind::Burn(damage) = effect.kind {
+                // count the burn ticks crossed this frame
+                let ticks = (effect.duration.elapsed_secs() / BURN_TICK).floor()
+                    - (before / BURN_TICK).floor();
+                enemy.health = enemy.health.saturating_sub(damage * ticks as u32);
+            }
+        }
+        effects.0.retain(|effect| !effect.duration.finished());
+    }
+}
+
+// a ring above the enemy for every effect on it
+fn draw_status_effects(mut gizmos: Gizmos, query: Query<(&Transform, &StatusEffects)>) {
+    for (transform, effects) in query.iter() {
+        for (i, effect) in effects.0.iter().enumerate() {
+            let position = transform.translation + Vec3::Y * (0.8 + i as f32 * 0.1);
+            gizmos.circle(
+                Isometry3d::new(position, Quat::from_rotation_x(FRAC_PI_2)),
+                0.25,
+                effect.color(),
+            );
+        }
+    }
+}
+
 fn target_death(
     mut commands: Commands,
     enemies: Query<(Entity, &Enemy)>,
@@ -243,17 +623,144 @@ fn target_death(
 
 fn end_wave(
     mut next_state: ResMut<NextState<GamePlayState>>,
-    time: Res<Time>,
-    mut wave_query: Query<&mut Wave>,
+    mut game_resources: ResMut<GameResources>,
+    spawner_query: Query<&EnemySpawner>,
     enemy_query: Query<Entity, With<Enemy>>,
 ) {
-    for mut wave in wave_query.iter_mut() {
-        wave.timer.tick(time.delta());
-        if wave.timer.finished() {
-            if enemy_query.is_empty() {
-                info!("Wave ended");
-                next_state.set(GamePlayState::Economy);
-            }
+    if spawner_query.iter().all(|spawner| spawner.remaining == 0) && enemy_query.is_empty() {
+        info!("Wave {} ended", game_resources.wave + 1);
+        game_resources.wave += 1;
+        next_state.set(GamePlayState::Economy);
+    }
+}
+
+// floods the wave with enemies to check the frame budget holds up under hundreds of them
+#[cfg(feature = "debug")]
+fn spawn_swarm(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    assets_enemies: Res<Assets<EnemyDetails>>,
+    spawn_points: Query<&Transform, With<SpawnPoint>>,
+) {
+    const SWARM_SIZE: u32 = 500;
+
+    if !keys.just_pressed(KeyCode::F8) {
+        return;
+    }
+    let Some((_, enemy)) = assets_enemies.iter().next() else {
+        return;
+    };
+    for spawn_transform in spawn_points.iter() {
+        commands.spawn((
+            Transform::from_translation(spawn_transform.translation),
+            EnemySpawner {
+                enemy: enemy.name.clone(),
+                remaining: SWARM_SIZE,
+                delay: Timer::from_seconds(0.0, TimerMode::Once),
+                interval: Timer::from_seconds(0.01, TimerMode::Repeating),
+            },
+            Name::new("Swarm Spawner"),
+        ));
+    }
+    info!("Spawning a swarm of {} {}", SWARM_SIZE, enemy.name);
+}
+
+// logs each time the smoothed frame time crosses the budget, with the enemies out at the time
+#[cfg(feature = "debug")]
+fn check_frame_budget(
+    diagnostics: Res<bevy::diagnostic::DiagnosticsStore>,
+    enemies: Query<(), With<Enemy>>,
+    mut over_budget: Local<bool>,
+) {
+    // 60 frames a second
+    const FRAME_BUDGET_MS: f64 = 1000.0 / 60.0;
+
+    let Some(frame_time) = diagnostics
+        .get(&bevy::diagnostic::FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+    else {
+        return;
+    };
+    let over = frame_time > FRAME_BUDGET_MS;
+    if over == *over_budget {
+        return;
+    }
+    *over_budget = over;
+    if over {
+        warn!(
+            "Frame time {:.1} ms is over the {:.1} ms budget with {} enemies",
+            frame_time,
+            FRAME_BUDGET_MS,
+            enemies.iter().count()
+        );
+    } else {
+        info!(
+            "Frame time {:.1} ms is back within budget with {} enemies",
+            frame_time,
+            enemies.iter().count()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(effects: &mut StatusEffects, seconds: f32) {
+        for effect in effects.0.iter_mut() {
+            effect.duration.tick(Duration::from_secs_f32(seconds));
+        }
+    }
+
+    #[test]
+    fn burns_stack_up_to_the_cap_then_refresh_the_oldest() {
+        let mut effects = StatusEffects::default();
+        for _ in 0..MAX_BURN_STACKS {
+            effects.apply(StatusEffect::new(StatusKind::Burn(1), 3.0));
+            tick(&mut effects, 1.0);
         }
+        effects.apply(StatusEffect::new(StatusKind::Burn(1), 3.0));
+
+        assert_eq!(effects.0.len(), MAX_BURN_STACKS);
+        let mut remaining = effects
+            .0
+            .iter()
+            .map(|effect| effect.duration.remaining_secs())
+            .collect::<Vec<_>>();
+        remaining.sort_by(f32::total_cmp);
+        // the oldest burn had run down and is back to three seconds, the others keep ticking
+        assert_eq!(remaining, vec![1.0, 2.0, 3.0]);
+        assert_eq!(effects.speed_multiplier(), 1.0);
+    }
+
+    #[test]
+    fn a_new_slow_refreshes_the_duration_and_keeps_the_stronger_slow() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusKind::Slow(0.4), 2.0));
+        tick(&mut effects, 1.5);
+        effects.apply(StatusEffect::new(StatusKind::Slow(0.2), 2.0));
+
+        assert_eq!(effects.0.len(), 1);
+        assert_eq!(effects.0[0].kind, StatusKind::Slow(0.4));
+        assert_eq!(effects.0[0].duration.remaining_secs(), 2.0);
+        assert!((effects.speed_multiplier() - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn a_stun_stops_the_enemy() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusKind::Slow(0.4), 2.0));
+        effects.apply(StatusEffect::new(StatusKind::Stun, 0.5));
+        assert_eq!(effects.speed_multiplier(), 0.0);
+
+        // a shorter stun does not cut the current one short
+        effects.apply(StatusEffect::new(StatusKind::Stun, 0.2));
+        let stuns = effects
+            .0
+            .iter()
+            .filter(|effect| effect.kind == StatusKind::Stun)
+            .collect::<Vec<_>>();
+        assert_eq!(stuns.len(), 1);
+        assert_eq!(stuns[0].duration.remaining_secs(), 0.5);
     }
 }
@@ -0,0 +1,58 @@
+use crate::game::{GameResources, RunSeed};
+
+use super::GameState;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, ui.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+fn ui(
+    mut contexts: EguiContexts,
+    game_resources: Res<GameResources>,
+    seed: Res<RunSeed>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+            ui.style_mut().spacing.item_spacing = egui::vec2(0.0, 10.0);
+
+            ui.add(egui::Label::new(
+                egui::RichText::new("Game Over").size(64.0),
+            ));
+
+            ui.add_space(10.0);
+
+            ui.label(
+                egui::RichText::new(format!("Waves cleared: {}", game_resources.wave())).size(24.0),
+            );
+            ui.label(egui::RichText::new(format!("Money: {}", game_resources.money())).size(24.0));
+            // enough to replay the run from the menu or with --seed
+            ui.label(egui::RichText::new(format!("Seed: {}", seed.0)).size(18.0));
+
+            ui.add_space(10.0);
+
+            // restarting replays the same seed, a new one can be picked from the menu
+            let restart = ui.add(egui::Button::new(egui::RichText::new("Restart").size(32.0)));
+            let menu = ui.add(egui::Button::new(
+                egui::RichText::new("Main Menu").size(24.0),
+            ));
+
+            if restart.clicked() {
+                next_state.set(GameState::Game);
+            }
+
+            if menu.clicked() {
+                next_state.set(GameState::Menu);
+            }
+        })
+    });
+}
@@ -1,6 +1,7 @@
 #[cfg(feature = "debug")]
 mod debug;
 mod game;
+mod gameover;
 mod input;
 mod menu;
 mod splash;
@@ -16,9 +17,17 @@ enum GameState {
     Splash,
     Menu,
     Game,
+    GameOver,
 }
 
 fn main() {
+    // `--simulate-dice <shape>` throws dice without a window and prints how fair they roll
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(config) = game::FairnessConfig::from_args() {
+        println!("{}", game::simulate_fairness(&config));
+        return;
+    }
+
     App::new()
         .add_plugins(
             DefaultPlugins
@@ -35,6 +44,7 @@ fn main() {
             splash::SplashPlugin,
             menu::MenuPlugin,
             game::GamePlugin,
+            gameover::GameOverPlugin,
             input::InputModeManagerPlugin,
             #[cfg(feature = "debug")]
             debug::DebugPlugin,
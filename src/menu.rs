@@ -1,4 +1,4 @@
-use crate::GAME_NAME;
+use crate::{game::RunSeed, GAME_NAME};
 
 use super::{despawn_screen, GameState};
 
@@ -24,8 +24,12 @@ fn ui(
     mut contexts: EguiContexts,
     mut next_state: ResMut<NextState<GameState>>,
     mut exit: EventWriter<AppExit>,
+    mut seed: ResMut<RunSeed>,
+    mut seed_text: Local<Option<String>>,
 ) {
     let ctx = contexts.ctx_mut();
+    let seed_text = seed_text.get_or_insert_with(|| seed.0.to_string());
+    let parsed_seed = seed_text.trim().parse::<u64>().ok();
 
     egui::CentralPanel::default().show(ctx, |ui| {
         ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
@@ -35,11 +39,28 @@ fn ui(
 
             ui.add_space(10.0);
 
-            let play = ui.add(egui::Button::new(egui::RichText::new("Play").size(32.0)));
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Seed").size(18.0));
+                ui.add(egui::TextEdit::singleline(seed_text).desired_width(180.0));
+                if ui.button("New").clicked() {
+                    *seed_text = rand::random::<u64>().to_string();
+                }
+            });
+            if parsed_seed.is_none() {
+                ui.label(egui::RichText::new("Seed must be a number").color(egui::Color32::RED));
+            }
+
+            let play = ui.add_enabled(
+                parsed_seed.is_some(),
+                egui::Button::new(egui::RichText::new("Play").size(32.0)),
+            );
             let quit = ui.add(egui::Button::new(egui::RichText::new("Quit").size(24.0)));
 
             if play.clicked() {
-                next_state.set(GameState::Game);
+                if let Some(parsed_seed) = parsed_seed {
+                    seed.0 = parsed_seed;
+                    next_state.set(GameState::Game);
+                }
             }
 
             if quit.clicked() {